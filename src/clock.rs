@@ -0,0 +1,79 @@
+//! Clocks used to timestamp events.
+
+use std::{
+    fmt::Debug,
+    panic::{RefUnwindSafe, UnwindSafe},
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+/// A source of timestamps for the events written to an `EventChannel`.
+///
+/// Timestamps are the time passed since an origin chosen by the clock, so
+/// they can only be compared with timestamps of the same clock.
+///
+/// Clocks have to be unwind safe, since the `EventChannel` is.
+pub trait Clock: Debug + Send + Sync + UnwindSafe + RefUnwindSafe {
+    /// Returns the current time.
+    fn now(&self) -> Duration;
+}
+
+/// A clock based on `std::time::Instant`, whose origin is the time it got
+/// created.
+#[derive(Clone, Copy, Debug)]
+pub struct InstantClock {
+    origin: Instant,
+}
+
+impl InstantClock {
+    /// Creates a new clock, starting at zero.
+    pub fn new() -> Self {
+        InstantClock {
+            origin: Instant::now(),
+        }
+    }
+}
+
+impl Default for InstantClock {
+    fn default() -> Self {
+        InstantClock::new()
+    }
+}
+
+impl Clock for InstantClock {
+    fn now(&self) -> Duration {
+        self.origin.elapsed()
+    }
+}
+
+/// A clock which only advances when told to, for deterministic tests.
+///
+/// Clones share the same time, so you can keep one to advance the clock
+/// after passing another one to the `EventChannel`.
+#[derive(Clone, Debug, Default)]
+pub struct ManualClock {
+    now: Arc<Mutex<Duration>>,
+}
+
+impl ManualClock {
+    /// Creates a new clock, starting at zero.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Advances the clock by `by`.
+    pub fn advance(&self, by: Duration) {
+        *self.now.lock().unwrap() += by;
+    }
+
+    /// Sets the clock to `now`.
+    pub fn set(&self, now: Duration) {
+        *self.now.lock().unwrap() = now;
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Duration {
+        *self.now.lock().unwrap()
+    }
+}
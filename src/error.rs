@@ -0,0 +1,113 @@
+//! Errors returned by the fallible operations of the `EventChannel`.
+
+use std::{
+    error::Error,
+    fmt::{self, Display, Formatter},
+};
+
+/// The error type returned by `EventChannel::try_read`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReadError {
+    /// The `ReaderId` was registered with a different channel.
+    ForeignChannel,
+    /// The `ReaderId` is not registered with the channel (anymore).
+    Unregistered,
+    /// The reader didn't read for longer than the reader expiry of the channel
+    /// allows, so it got deactivated.
+    ///
+    /// An expired `ReaderId` stays expired; register a new reader instead.
+    Expired,
+    /// The events can't be moved out of the channel, because other readers
+    /// haven't read them yet or the channel retains them.
+    Shared,
+    /// The reader fell so far behind a bounded channel that some of the events
+    /// it hadn't read yet have been overwritten.
+    ///
+    /// The reader has been moved to the oldest event still retained by the
+    /// channel, so the next read continues from there.
+    Lagged {
+        /// The number of events the reader missed.
+        missed: usize,
+    },
+}
+
+impl Display for ReadError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            ReadError::ForeignChannel => {
+                f.write_str("`ReaderId` was not allocated by this `EventChannel`")
+            }
+            ReadError::Unregistered => f.write_str("`ReaderId` is not registered"),
+            ReadError::Expired => f.write_str("`ReaderId` expired because it wasn't read"),
+            ReadError::Shared => f.write_str("events are still needed by the channel"),
+            ReadError::Lagged { missed } => {
+                write!(f, "reader lagged behind and missed {} events", missed)
+            }
+        }
+    }
+}
+
+impl Error for ReadError {}
+
+/// The error type returned by `EventChannel::seek` and
+/// `EventChannel::restore`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SeekError {
+    /// The `ReaderCursor` was taken from a different channel.
+    ForeignCursor,
+    /// The event has already been overwritten.
+    Overwritten {
+        /// The sequence number of the oldest event still stored.
+        oldest: u64,
+    },
+    /// The event hasn't been written yet.
+    NotWritten {
+        /// The sequence number the next written event will get.
+        next: u64,
+    },
+}
+
+impl Display for SeekError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            SeekError::ForeignCursor => {
+                f.write_str("cursor was not taken from this `EventChannel`")
+            }
+            SeekError::Overwritten { oldest } => write!(
+                f,
+                "event has been overwritten, the oldest stored event is {}",
+                oldest
+            ),
+            SeekError::NotWritten { next } => write!(
+                f,
+                "event has not been written yet, the next event will be {}",
+                next
+            ),
+        }
+    }
+}
+
+impl Error for SeekError {}
+
+/// The error returned by the `try_*_write` methods of `EventChannel`.
+///
+/// A write is rejected if the channel reached its maximum capacity and
+/// writing would overwrite events some reader hasn't read yet. The events
+/// which couldn't be written are handed back unchanged.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Full<T>(pub T);
+
+impl<T> Full<T> {
+    /// Returns the events which couldn't be written.
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> Display for Full<T> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str("event channel is full")
+    }
+}
+
+impl<T: fmt::Debug> Error for Full<T> {}
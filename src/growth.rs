@@ -0,0 +1,93 @@
+//! Policies deciding how much the buffer of an `EventChannel` grows.
+
+use std::{
+    cmp,
+    fmt::Debug,
+    panic::{RefUnwindSafe, UnwindSafe},
+};
+
+/// Decides the new capacity whenever an `EventChannel` has to grow to avoid
+/// overwriting events some reader hasn't read yet.
+///
+/// Policies have to be unwind safe, since the `EventChannel` is.
+pub trait GrowthPolicy: Debug + Send + Sync + UnwindSafe + RefUnwindSafe {
+    /// Returns the new capacity of a buffer with `capacity` elements, which
+    /// needs room for at least `required` elements.
+    ///
+    /// A result smaller than `required` is treated like `required`.
+    fn grow(&self, capacity: usize, required: usize) -> usize;
+
+    /// Returns the capacity the buffer never grows beyond, if any.
+    ///
+    /// Once it's reached, writes overwrite the oldest events, just like in a
+    /// bounded channel.
+    fn max_capacity(&self) -> Option<usize> {
+        None
+    }
+}
+
+/// Doubles the capacity until the required elements fit.
+///
+/// This is the default policy.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Doubling;
+
+impl GrowthPolicy for Doubling {
+    fn grow(&self, capacity: usize, required: usize) -> usize {
+        // Make sure size' = 2^n * size
+        let mut size = 2 * capacity;
+        while size < required {
+            size *= 2;
+        }
+
+        size
+    }
+}
+
+/// Grows the capacity in steps of a fixed number of elements.
+#[derive(Clone, Copy, Debug)]
+pub struct Linear {
+    step: usize,
+}
+
+impl Linear {
+    /// Creates a policy which grows by multiples of `step` elements.
+    pub fn new(step: usize) -> Self {
+        assert!(step > 0, "step has to be at least 1");
+
+        Linear { step }
+    }
+}
+
+impl GrowthPolicy for Linear {
+    fn grow(&self, capacity: usize, required: usize) -> usize {
+        let steps = required.saturating_sub(capacity).div_ceil(self.step);
+
+        capacity + cmp::max(steps, 1) * self.step
+    }
+}
+
+/// Doubles the capacity, but never grows beyond a maximum.
+///
+/// This is what `EventChannel::bounded` uses.
+#[derive(Clone, Copy, Debug)]
+pub struct FixedMax {
+    max: usize,
+}
+
+impl FixedMax {
+    /// Creates a policy which never grows beyond `max` elements.
+    pub fn new(max: usize) -> Self {
+        FixedMax { max }
+    }
+}
+
+impl GrowthPolicy for FixedMax {
+    fn grow(&self, capacity: usize, required: usize) -> usize {
+        cmp::min(Doubling.grow(capacity, required), self.max)
+    }
+
+    fn max_capacity(&self) -> Option<usize> {
+        Some(self.max)
+    }
+}
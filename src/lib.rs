@@ -5,10 +5,31 @@
 
 #![warn(missing_docs)]
 
-pub use crate::storage::{ReaderId, StorageIterator as EventIterator};
+pub use crate::{
+    clock::{Clock, InstantClock, ManualClock},
+    error::{Full, ReadError, SeekError},
+    growth::{Doubling, FixedMax, GrowthPolicy, Linear},
+    storage::{
+        BatchedIterator as BatchedEventIterator, DrainIterator as DrainEventIterator,
+        FilteredIterator as FilteredEventIterator, FilteredReaderId, ReaderCursor, ReaderId,
+        SequencedIterator as SequencedEventIterator, StorageIterator as EventIterator,
+        TimestampedIterator as TimestampedEventIterator, TrackedIterator as TrackedEventIterator,
+        Transaction as ReadTransaction,
+    },
+};
+
+use std::{
+    iter::{Cloned, FromIterator},
+    ops::Range,
+    panic::{RefUnwindSafe, UnwindSafe},
+    sync::Arc,
+};
 
 use crate::storage::RingBuffer;
 
+mod clock;
+mod error;
+mod growth;
 mod storage;
 mod util;
 
@@ -33,6 +54,22 @@ const DEFAULT_CAPACITY: usize = 64;
 /// all readers have read the event which is about to be overwritten. In case
 /// the answer is "No", it will grow the buffer so no events get overwritten.
 ///
+/// A bounded channel (see `EventChannel::bounded`) only grows up to its
+/// maximum capacity. Once that is reached, the oldest events get overwritten
+/// even if a slow reader didn't observe them yet; that reader is then informed
+/// by `EventChannel::try_read` about how many events it missed. If losing
+/// events is not acceptable, `EventChannel::try_iter_write` rejects writes
+/// that would overwrite unread events instead.
+///
+/// Every event gets a sequence number when it's written, starting at zero and
+/// increasing by one per event. It's returned by the write methods and can be
+/// obtained when reading with `EventIterator::sequenced`. Readers can be moved
+/// to any event still stored in the buffer using `EventChannel::seek`.
+///
+/// Writes are panic-safe: if the iterator passed to a write (or the `Drop` of
+/// an overwritten event) panics, the events written up to that point form a
+/// complete write, and the channel can be used as usual afterwards.
+///
 /// Readers are stores in the `EventChannel` itself, because we need to access
 /// their position in a write, so we can check what's described above. Thus, you
 /// only get a `ReaderId` as a handle.
@@ -117,6 +154,51 @@ where
         }
     }
 
+    /// Create a new bounded `EventChannel`, which never grows beyond
+    /// `max_capacity`.
+    ///
+    /// Once the buffer is full, writing overwrites the oldest events, even if
+    /// some readers haven't read them yet. Use `try_read` to detect that a
+    /// reader lagged behind.
+    pub fn bounded(max_capacity: usize) -> Self {
+        Self::with_capacity_bounded(DEFAULT_CAPACITY.min(max_capacity), max_capacity)
+    }
+
+    /// Create a new bounded `EventChannel` with the given starting capacity,
+    /// which never grows beyond `max_capacity`.
+    ///
+    /// See `bounded` for details.
+    pub fn with_capacity_bounded(size: usize, max_capacity: usize) -> Self {
+        Self {
+            storage: RingBuffer::new_bounded(size, max_capacity),
+        }
+    }
+
+    /// Create a new `EventChannel` with the given starting capacity, which
+    /// grows as decided by `policy`.
+    ///
+    /// By default, channels use `Doubling`; `Linear` keeps memory usage
+    /// tight, while `FixedMax` never grows beyond a maximum capacity (like
+    /// `bounded`). You can implement `GrowthPolicy` yourself to pre-size
+    /// more aggressively.
+    ///
+    /// ```
+    /// use shrev::{EventChannel, Linear};
+    ///
+    /// let mut channel = EventChannel::with_growth_policy(16, Linear::new(16));
+    /// let _reader = channel.register_reader();
+    /// channel.iter_write(0..20);
+    /// assert_eq!(channel.capacity(), 32);
+    /// ```
+    pub fn with_growth_policy<P>(size: usize, policy: P) -> Self
+    where
+        P: GrowthPolicy + 'static,
+    {
+        Self {
+            storage: RingBuffer::with_growth_policy(size, Box::new(policy)),
+        }
+    }
+
     /// Returns `true` if any reader would observe an additional event.
     ///
     /// This can be used to skip calls to `iter_write` in case the event
@@ -125,6 +207,25 @@ where
         self.storage.would_write()
     }
 
+    /// Returns `true` if any reader would observe `event` if it was written.
+    ///
+    /// Other than `would_write`, this takes the filters of readers registered
+    /// with `register_filtered_reader` into account, so it only returns `true`
+    /// if some reader is unfiltered or its filter accepts `event`.
+    pub fn would_write_event(&mut self, event: &E) -> bool {
+        self.storage.would_write_elem(event)
+    }
+
+    /// Returns `true` if any reader might observe an additional event.
+    ///
+    /// Unlike `would_write`, this only needs an immutable reference. In
+    /// exchange, readers which have been dropped since the channel was last
+    /// accessed mutably are still counted, so this can return `true` even
+    /// though `would_write` would return `false`.
+    pub fn might_write(&self) -> bool {
+        self.storage.might_write()
+    }
+
     /// Register a new reader.
     ///
     /// To be able to read events, a reader id is required. This is because
@@ -141,32 +242,365 @@ where
         self.storage.new_reader_id()
     }
 
+    /// Register a new reader, which only receives the events accepted by
+    /// `filter`.
+    ///
+    /// The filter is stored in the channel and applied when reading with
+    /// `read_filtered`, so systems only interested in a few events of a busy
+    /// channel don't have to discard the rest themselves.
+    ///
+    /// ```
+    /// use shrev::EventChannel;
+    ///
+    /// let mut channel = EventChannel::new();
+    /// let mut reader_id = channel.register_filtered_reader(|event: &i32| *event > 2);
+    /// channel.iter_write(0..5);
+    ///
+    /// assert_eq!(
+    ///     channel.read_filtered(&mut reader_id).cloned().collect::<Vec<_>>(),
+    ///     vec![3, 4]
+    /// );
+    /// ```
+    pub fn register_filtered_reader<F>(&mut self, filter: F) -> FilteredReaderId<E>
+    where
+        F: Fn(&E) -> bool + Send + Sync + UnwindSafe + RefUnwindSafe + 'static,
+    {
+        self.storage.new_filtered_reader_id(filter)
+    }
+
+    /// Register a new reader, which also receives the last `num` events
+    /// written before its creation.
+    ///
+    /// If fewer events are stored in the channel, the reader starts at the
+    /// oldest one. Use `set_retention` to make sure enough events are kept.
+    pub fn register_reader_with_history(&mut self, num: usize) -> ReaderId<E> {
+        self.storage.new_reader_id_with_history(num)
+    }
+
+    /// Register a new reader, which starts at the oldest event still stored in
+    /// the channel.
+    pub fn register_reader_from_oldest(&mut self) -> ReaderId<E> {
+        self.storage.new_reader_id_at(0)
+    }
+
+    /// Register a new reader, which starts exactly where `reader_id` currently
+    /// is.
+    ///
+    /// Both readers receive the same events on their next `read`, but are
+    /// independent from each other afterwards. The new reader also inherits
+    /// the events `reader_id` missed (see `try_read`) and is paused if
+    /// `reader_id` is.
+    pub fn fork_reader(&mut self, reader_id: &ReaderId<E>) -> ReaderId<E> {
+        self.storage.fork_reader_id(reader_id)
+    }
+
+    /// Pause `reader_id`, so it stops keeping events from being overwritten.
+    ///
+    /// This is meant for readers of systems which are disabled for a while.
+    /// A paused reader doesn't observe any events, doesn't make the channel
+    /// grow and isn't counted by `would_write`, but it keeps its subscription
+    /// until it's dropped. Use `resume` to receive events again.
+    ///
+    /// While a reader is paused, reading with it returns no events. Moving it
+    /// with `seek` or `restore` only changes the event `resume_from_oldest`
+    /// starts at, since nothing keeps those events from being overwritten.
+    pub fn pause(&mut self, reader_id: &mut ReaderId<E>) {
+        self.storage.pause(reader_id);
+    }
+
+    /// Resume a paused `reader_id`, which then receives the events written
+    /// from now on.
+    ///
+    /// Returns the number of events written since it was paused (including
+    /// the events it hadn't read yet at that point), or `0` if it wasn't
+    /// paused.
+    ///
+    /// ```
+    /// use shrev::EventChannel;
+    ///
+    /// let mut channel = EventChannel::new();
+    /// let mut reader_id = channel.register_reader();
+    ///
+    /// channel.pause(&mut reader_id);
+    /// channel.iter_write(0..4);
+    /// assert_eq!(channel.resume(&mut reader_id), 4);
+    ///
+    /// channel.single_write(4);
+    /// assert_eq!(channel.read(&mut reader_id).cloned().collect::<Vec<_>>(), vec![4]);
+    /// ```
+    pub fn resume(&mut self, reader_id: &mut ReaderId<E>) -> usize {
+        self.storage.resume(reader_id)
+    }
+
+    /// Resume a paused `reader_id` like `resume`, but continue at the oldest
+    /// event it missed which is still stored in the channel.
+    ///
+    /// Returns the number of events which got overwritten in the meantime and
+    /// are therefore skipped.
+    pub fn resume_from_oldest(&mut self, reader_id: &mut ReaderId<E>) -> usize {
+        self.storage.resume_from_oldest(reader_id)
+    }
+
+    /// Returns the number of events which are kept for late readers.
+    ///
+    /// See `set_retention`.
+    pub fn retention(&self) -> usize {
+        self.storage.retention()
+    }
+
+    /// Make sure the last `num` events are kept, even if every reader has
+    /// already read them.
+    ///
+    /// Normally, events get overwritten as soon as all readers have observed
+    /// them, so readers registered later have no way to see them. With a
+    /// retention, readers registered with `register_reader_with_history` (or
+    /// `register_reader_from_oldest`) get a replay of up to `num` events
+    /// written before.
+    ///
+    /// In a bounded channel, `num` has to be smaller than the maximum
+    /// capacity.
+    ///
+    /// ```
+    /// use shrev::EventChannel;
+    ///
+    /// let mut channel = EventChannel::with_capacity(4);
+    /// channel.set_retention(8);
+    /// channel.iter_write(0..10);
+    ///
+    /// let mut late_reader = channel.register_reader_with_history(8);
+    /// assert_eq!(
+    ///     channel.read(&mut late_reader).cloned().collect::<Vec<_>>(),
+    ///     (2..10).collect::<Vec<_>>()
+    /// );
+    /// ```
+    pub fn set_retention(&mut self, num: usize) {
+        self.storage.set_retention(num);
+    }
+
+    /// Returns the number of events the channel can hold before it has to
+    /// grow (or overwrite events, if bounded).
+    pub fn capacity(&self) -> usize {
+        self.storage.capacity()
+    }
+
+    /// Shrink the capacity of the channel as much as possible.
+    ///
+    /// See `shrink_to`.
+    pub fn shrink_to_fit(&mut self) {
+        self.storage.shrink_to(0);
+    }
+
+    /// Shrink the capacity of the channel, but not below `min_capacity`.
+    ///
+    /// The channel keeps all events some reader hasn't read yet, as well as
+    /// the events retained with `set_retention`, so the capacity might stay
+    /// higher. Readers continue right where they were.
+    ///
+    /// ```
+    /// use shrev::EventChannel;
+    ///
+    /// let mut channel = EventChannel::with_capacity(16);
+    /// let mut reader = channel.register_reader();
+    /// channel.iter_write(0..1000);
+    /// channel.read(&mut reader);
+    /// channel.iter_write(0..4);
+    ///
+    /// channel.shrink_to(8);
+    /// assert_eq!(channel.capacity(), 8);
+    /// assert_eq!(channel.read(&mut reader).len(), 4);
+    /// ```
+    pub fn shrink_to(&mut self, min_capacity: usize) {
+        self.storage.shrink_to(min_capacity);
+    }
+
+    /// Returns `true` if the channel shrinks back to its initial capacity
+    /// automatically.
+    ///
+    /// See `set_auto_shrink`.
+    pub fn auto_shrink(&self) -> bool {
+        self.storage.auto_shrink()
+    }
+
+    /// Make the channel shrink back to the capacity it was created with,
+    /// once every reader has caught up after it grew.
+    ///
+    /// This is checked when writing, so the allocation of a single spike of
+    /// events doesn't stick around. Don't enable this for channels which
+    /// regularly need to grow, since every shrink reallocates the buffer.
+    /// With a `retention` of at least the initial capacity, the channel
+    /// keeps room for the retained events and one more.
+    pub fn set_auto_shrink(&mut self, auto_shrink: bool) {
+        self.storage.set_auto_shrink(auto_shrink);
+    }
+
+    /// Make every following write record the current time of `clock` for its
+    /// events, so they can be read with `read_timestamped`.
+    ///
+    /// The timestamps are stored next to the events, so `E` doesn't need a
+    /// field for them. All events of one write get the same timestamp.
+    ///
+    /// Use `InstantClock` for the actual time, or `ManualClock` to control
+    /// the time in tests.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if any event has been written to the channel already.
+    pub fn set_clock<C>(&mut self, clock: C)
+    where
+        C: Clock + 'static,
+    {
+        self.storage.set_clock(Box::new(clock));
+    }
+
+    /// Returns the number of writes after which a reader which didn't read
+    /// expires, if expiry is enabled.
+    ///
+    /// See `set_reader_expiry`.
+    pub fn reader_expiry(&self) -> Option<usize> {
+        self.storage.reader_expiry()
+    }
+
+    /// Deactivate readers which didn't read for more than `writes` writes, or
+    /// disable that with `None` (the default).
+    ///
+    /// A `ReaderId` which is kept around but never read makes the channel
+    /// grow without bounds. With an expiry, such a reader gets removed just
+    /// like a dropped one, so it stops holding back events. Any access to the
+    /// reader through `&mut ReaderId` (like `read`, `seek` or `resume`) counts
+    /// as reading; paused readers never expire.
+    ///
+    /// Reading with an expired reader makes `try_read` return
+    /// `ReadError::Expired` (and `read` panic).
+    ///
+    /// ```
+    /// use shrev::{EventChannel, ReadError};
+    ///
+    /// let mut channel = EventChannel::new();
+    /// channel.set_reader_expiry(Some(2));
+    ///
+    /// let mut reader = channel.register_reader();
+    /// channel.single_write(1);
+    /// channel.single_write(2);
+    /// assert!(channel.try_read(&mut reader).is_ok());
+    ///
+    /// channel.iter_write(3..6);
+    /// channel.iter_write(6..9);
+    /// channel.iter_write(9..12);
+    /// assert_eq!(channel.try_read(&mut reader).err(), Some(ReadError::Expired));
+    /// ```
+    pub fn set_reader_expiry(&mut self, writes: Option<usize>) {
+        self.storage.set_reader_expiry(writes);
+    }
+
     /// Write a slice of events into storage
     #[deprecated(note = "please use `iter_write` instead")]
     pub fn slice_write(&mut self, events: &[E])
     where
         E: Clone,
     {
-        self.storage.iter_write(events.into_iter().cloned());
+        self.storage.iter_write(events.iter().cloned());
     }
 
-    /// Write an iterator of events into storage
-    pub fn iter_write<I>(&mut self, iter: I)
+    /// Write an iterator of events into storage.
+    ///
+    /// Any iterator can be written; if its exact length is known upfront
+    /// (like for an `ExactSizeIterator`), the required space is reserved at
+    /// once, otherwise the channel grows while writing.
+    ///
+    /// Returns the range of sequence numbers assigned to the events.
+    pub fn iter_write<I>(&mut self, iter: I) -> Range<u64>
     where
         I: IntoIterator<Item = E>,
-        I::IntoIter: ExactSizeIterator,
     {
-        self.storage.iter_write(iter);
+        self.storage.iter_write(iter)
     }
 
     /// Drain a vector of events into storage.
-    pub fn drain_vec_write(&mut self, events: &mut Vec<E>) {
-        self.storage.drain_vec_write(events);
+    ///
+    /// Returns the range of sequence numbers assigned to the events.
+    pub fn drain_vec_write(&mut self, events: &mut Vec<E>) -> Range<u64> {
+        self.storage.drain_vec_write(events)
     }
 
     /// Write a single event into storage.
-    pub fn single_write(&mut self, event: E) {
-        self.storage.single_write(event);
+    ///
+    /// Returns the sequence number assigned to the event.
+    pub fn single_write(&mut self, event: E) -> u64 {
+        self.storage.single_write(event)
+    }
+
+    /// Write `num` events created by `f`, which gets the event each new one
+    /// overwrites, so its allocations can be reused.
+    ///
+    /// `f` only ever gets events that every reader has read already (or
+    /// missed, in a bounded channel). If the slot of the new event was never
+    /// used or the channel just grew, it gets `None` instead.
+    ///
+    /// Returns the range of sequence numbers assigned to the events.
+    pub fn write_with<F>(&mut self, num: usize, f: F) -> Range<u64>
+    where
+        F: FnMut(Option<E>) -> E,
+    {
+        self.storage.write_with(num, f)
+    }
+
+    /// Write a single event created by `f`, which gets the event it
+    /// overwrites, so its allocations can be reused.
+    ///
+    /// See `write_with` for details.
+    ///
+    /// Returns the sequence number assigned to the event.
+    ///
+    /// ```
+    /// use shrev::EventChannel;
+    ///
+    /// let mut channel = EventChannel::with_capacity(2);
+    /// let mut reader = channel.register_reader();
+    ///
+    /// for frame in 0..10 {
+    ///     channel.single_write_with(|old: Option<Vec<u32>>| {
+    ///         let mut input = old.unwrap_or_default();
+    ///         input.clear();
+    ///         input.extend(0..frame);
+    ///         input
+    ///     });
+    ///
+    ///     let input = channel.read(&mut reader).next().unwrap();
+    ///     assert_eq!(input.len(), frame as usize);
+    /// }
+    /// ```
+    pub fn single_write_with<F>(&mut self, f: F) -> u64
+    where
+        F: FnOnce(Option<E>) -> E,
+    {
+        self.storage.single_write_with(f)
+    }
+
+    /// Write an iterator of events into storage, unless that would exceed the
+    /// maximum capacity of a bounded channel.
+    ///
+    /// Instead of overwriting events some reader hasn't read yet, this hands
+    /// back the iterator untouched inside of `Full`, so no event is lost.
+    /// Unbounded channels grow instead, so this always succeeds for them.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if the iterator yields more events than its `len` reported. The
+    /// events up to `len` have been written at that point.
+    pub fn try_iter_write<I>(&mut self, iter: I) -> Result<Range<u64>, Full<I::IntoIter>>
+    where
+        I: IntoIterator<Item = E>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        self.storage.try_iter_write(iter)
+    }
+
+    /// Write a single event into storage, unless that would exceed the maximum
+    /// capacity of a bounded channel.
+    ///
+    /// See `try_iter_write` for details.
+    pub fn try_single_write(&mut self, event: E) -> Result<u64, Full<E>> {
+        self.storage.try_single_write(event)
     }
 
     /// Read any events that have been written to storage since the last read
@@ -178,9 +612,417 @@ where
     /// without iterating the result won't preserve the events returned. You
     /// need to iterate all the events as soon as you got them from this
     /// method. This behavior is equivalent to e.g. `Vec::drain`.
-    pub fn read(&self, reader_id: &mut ReaderId<E>) -> EventIterator<E> {
+    ///
+    /// The returned iterator supports random access: `EventIterator::get`,
+    /// `nth`, `skip` and `last` take constant time, and it can be reversed to
+    /// process the newest events first.
+    ///
+    /// In a bounded channel, events the reader missed because they got
+    /// overwritten are skipped silently; use `try_read` to detect that.
+    ///
+    /// ## Panics
+    ///
+    /// Panics if `reader_id` wasn't registered with this channel; see
+    /// `try_read` for a non-panicking version.
+    pub fn read(&self, reader_id: &mut ReaderId<E>) -> EventIterator<'_, E> {
         self.storage.read(reader_id)
     }
+
+    /// Move the events that have been written since the last read with
+    /// `reader_id` out of the channel, instead of borrowing them.
+    ///
+    /// This avoids cloning large events, but only works if `reader_id` is the
+    /// only reader which still needs them and the channel doesn't retain any
+    /// events (see `set_retention`). Otherwise `ReadError::Shared` is
+    /// returned, and you need to `read` and clone the events instead.
+    ///
+    /// All events currently stored in the channel are removed; the ones the
+    /// returned iterator doesn't yield are dropped along with it.
+    ///
+    /// ```
+    /// use shrev::{EventChannel, ReadError};
+    ///
+    /// let mut channel = EventChannel::new();
+    /// let mut reader = channel.register_reader();
+    /// channel.iter_write(vec![String::from("a"), String::from("b")]);
+    ///
+    /// let events: Vec<String> = channel.drain(&mut reader).unwrap().collect();
+    /// assert_eq!(events, vec!["a", "b"]);
+    ///
+    /// let _other = channel.register_reader();
+    /// channel.single_write(String::from("c"));
+    /// assert_eq!(channel.drain(&mut reader).err(), Some(ReadError::Shared));
+    /// ```
+    pub fn drain(
+        &mut self,
+        reader_id: &mut ReaderId<E>,
+    ) -> Result<DrainEventIterator<'_, E>, ReadError> {
+        self.storage.drain(reader_id)
+    }
+
+    /// Read any events that have been written to storage since the last read
+    /// with `reader_id`, like `read` does, but as two slices.
+    ///
+    /// Since the channel uses a ring buffer, the events can wrap around its
+    /// end; in that case, the second slice holds the newer events. Otherwise
+    /// it's empty. See `VecDeque::as_slices`.
+    ///
+    /// ```
+    /// use shrev::EventChannel;
+    ///
+    /// let mut channel = EventChannel::with_capacity(4);
+    /// let mut reader = channel.register_reader();
+    /// channel.iter_write(0..3);
+    /// channel.read(&mut reader);
+    /// channel.iter_write(3..6);
+    ///
+    /// let (first, second) = channel.read_slices(&mut reader);
+    /// assert_eq!([first, second].concat(), vec![3, 4, 5]);
+    /// ```
+    pub fn read_slices(&self, reader_id: &mut ReaderId<E>) -> (&[E], &[E]) {
+        self.storage.read_slices(reader_id)
+    }
+
+    /// Read any events that have been written to storage since the last read
+    /// with `reader_id`, like `read` does, but grouped by the write call they
+    /// were written with.
+    ///
+    /// Every item is an `EventIterator` over the events of one write, in the
+    /// order they were written. If some events of a write were overwritten
+    /// before the reader got to them (see `bounded`), only the remaining ones
+    /// are returned.
+    ///
+    /// ```
+    /// use shrev::EventChannel;
+    ///
+    /// let mut channel = EventChannel::new();
+    /// let mut reader = channel.register_reader();
+    /// channel.iter_write(vec![1, 2]);
+    /// channel.single_write(3);
+    ///
+    /// let batches: Vec<Vec<i32>> = channel
+    ///     .read_batches(&mut reader)
+    ///     .map(|batch| batch.cloned().collect())
+    ///     .collect();
+    /// assert_eq!(batches, vec![vec![1, 2], vec![3]]);
+    /// ```
+    pub fn read_batches(&self, reader_id: &mut ReaderId<E>) -> BatchedEventIterator<'_, E> {
+        self.storage.read_batches(reader_id)
+    }
+
+    /// Read any events that have been written to storage since the last read
+    /// with `reader_id`, like `read` does, but together with the time they
+    /// were written at.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    ///
+    /// use shrev::{EventChannel, ManualClock};
+    ///
+    /// let clock = ManualClock::new();
+    /// let mut channel = EventChannel::new();
+    /// channel.set_clock(clock.clone());
+    /// let mut reader = channel.register_reader();
+    ///
+    /// channel.single_write("a");
+    /// clock.advance(Duration::from_millis(5));
+    /// channel.single_write("b");
+    ///
+    /// let events: Vec<_> = channel.read_timestamped(&mut reader).collect();
+    /// assert_eq!(
+    ///     events,
+    ///     vec![(Duration::ZERO, &"a"), (Duration::from_millis(5), &"b")]
+    /// );
+    /// ```
+    ///
+    /// ## Panics
+    ///
+    /// Panics if no clock has been set with `set_clock`.
+    pub fn read_timestamped(&self, reader_id: &mut ReaderId<E>) -> TimestampedEventIterator<'_, E> {
+        self.storage.read_timestamped(reader_id)
+    }
+
+    /// Read the events accepted by the filter of `reader_id` that have been
+    /// written to storage since its last read.
+    ///
+    /// Just like `read`, this advances the reader past all events, including
+    /// the ones rejected by the filter.
+    pub fn read_filtered(
+        &self,
+        reader_id: &mut FilteredReaderId<E>,
+    ) -> FilteredEventIterator<'_, E> {
+        self.storage.read_filtered(reader_id)
+    }
+
+    /// Read the events accepted by the filter of `reader_id` like
+    /// `read_filtered`, but return errors (including missed events) like
+    /// `try_read` does.
+    ///
+    /// To use other methods taking a `ReaderId`, like `pending` or `pause`,
+    /// see `FilteredReaderId::reader_id_mut`.
+    pub fn try_read_filtered(
+        &self,
+        reader_id: &mut FilteredReaderId<E>,
+    ) -> Result<FilteredEventIterator<'_, E>, ReadError> {
+        self.storage.try_read_filtered(reader_id)
+    }
+
+    /// Read any events that have been written to storage since the last read
+    /// with `reader_id`, but only consume the events you actually iterate.
+    ///
+    /// Unlike `read`, the position of the reader is updated once the returned
+    /// iterator gets dropped, and only advances past the events that were
+    /// yielded by it. Events you didn't get to are returned again by the next
+    /// read, which makes it possible to bail out in the middle of a batch.
+    /// Missed events of a bounded channel are still reported by `try_read`
+    /// afterwards.
+    ///
+    /// ```
+    /// use shrev::EventChannel;
+    ///
+    /// let mut channel = EventChannel::new();
+    /// let mut reader_id = channel.register_reader();
+    /// channel.iter_write(0..4);
+    ///
+    /// for &event in channel.read_tracked(&mut reader_id) {
+    ///     if event == 1 {
+    ///         break;
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(channel.read(&mut reader_id).cloned().collect::<Vec<_>>(), vec![2, 3]);
+    /// ```
+    pub fn read_tracked<'a>(
+        &'a self,
+        reader_id: &'a mut ReaderId<E>,
+    ) -> TrackedEventIterator<'a, E> {
+        self.storage.read_tracked(reader_id)
+    }
+
+    /// Start reading the events that have been written to storage since the
+    /// last read with `reader_id`, without consuming them yet.
+    ///
+    /// The reader only advances once `ReadTransaction::commit` is called.
+    /// Rolling the transaction back (or just dropping it) leaves the reader
+    /// where it was, so the same events are returned by the next read. Until
+    /// then, the events count as unread, so they won't get overwritten.
+    ///
+    /// This is useful for at-least-once processing, where handling a batch of
+    /// events can fail and needs to be retried. Missed events of a bounded
+    /// channel are still reported by `try_read` after a commit.
+    ///
+    /// ```
+    /// use shrev::EventChannel;
+    ///
+    /// let mut channel = EventChannel::new();
+    /// let mut reader_id = channel.register_reader();
+    /// channel.iter_write(0..4);
+    ///
+    /// let transaction = channel.read_transaction(&mut reader_id);
+    /// assert_eq!(transaction.len(), 4);
+    /// transaction.rollback();
+    ///
+    /// let transaction = channel.read_transaction(&mut reader_id);
+    /// let sum: i32 = transaction.iter().sum();
+    /// assert_eq!(sum, 6);
+    /// transaction.commit();
+    ///
+    /// assert_eq!(channel.read(&mut reader_id).len(), 0);
+    /// ```
+    pub fn read_transaction<'a>(
+        &'a self,
+        reader_id: &'a mut ReaderId<E>,
+    ) -> ReadTransaction<'a, E> {
+        self.storage.read_transaction(reader_id)
+    }
+
+    /// Move `reader_id` so that its next read starts at the event with the
+    /// sequence number `seq`.
+    ///
+    /// This works in both directions, as long as the event is still stored in
+    /// the channel; seeking to the sequence number of the next write makes the
+    /// reader skip all pending events. Events which already got overwritten
+    /// result in `SeekError::Overwritten`.
+    ///
+    /// ```
+    /// use shrev::EventChannel;
+    ///
+    /// let mut channel = EventChannel::new();
+    /// let mut reader_id = channel.register_reader();
+    /// let seqs = channel.iter_write(0..4);
+    /// assert_eq!(channel.read(&mut reader_id).len(), 4);
+    ///
+    /// channel.seek(&mut reader_id, seqs.start + 2).unwrap();
+    /// assert_eq!(
+    ///     channel.read(&mut reader_id).sequenced().collect::<Vec<_>>(),
+    ///     vec![(seqs.start + 2, &2), (seqs.start + 3, &3)]
+    /// );
+    /// ```
+    pub fn seek(&mut self, reader_id: &mut ReaderId<E>, seq: u64) -> Result<(), SeekError> {
+        self.storage.seek(reader_id, seq)
+    }
+
+    /// Capture the current position of `reader_id`.
+    ///
+    /// The returned cursor can be passed to `restore` later on to read the
+    /// same events again, e.g. to re-run a system over the same input. With
+    /// the `serde` feature enabled, cursors can also be serialized, but they
+    /// can only be restored while the channel they belong to exists.
+    pub fn cursor(&self, reader_id: &ReaderId<E>) -> ReaderCursor {
+        self.storage.cursor(reader_id)
+    }
+
+    /// Move `reader_id` to the position captured by `cursor`.
+    ///
+    /// Fails with `SeekError::ForeignCursor` if the cursor was taken from
+    /// another channel, and with `SeekError::Overwritten` if the events after
+    /// it are no longer stored.
+    ///
+    /// ```
+    /// use shrev::EventChannel;
+    ///
+    /// let mut channel = EventChannel::new();
+    /// let mut reader_id = channel.register_reader();
+    /// channel.iter_write(0..4);
+    ///
+    /// let cursor = channel.cursor(&reader_id);
+    /// let first = channel.read(&mut reader_id).cloned().collect::<Vec<_>>();
+    ///
+    /// channel.restore(&mut reader_id, cursor).unwrap();
+    /// let second = channel.read(&mut reader_id).cloned().collect::<Vec<_>>();
+    /// assert_eq!(first, second);
+    /// ```
+    pub fn restore(
+        &mut self,
+        reader_id: &mut ReaderId<E>,
+        cursor: ReaderCursor,
+    ) -> Result<(), SeekError> {
+        self.storage.restore(reader_id, cursor)
+    }
+
+    /// Returns the number of events `reader_id` would get by the next `read`.
+    ///
+    /// This does not advance the reader.
+    pub fn pending(&self, reader_id: &ReaderId<E>) -> usize {
+        self.storage.pending(reader_id)
+    }
+
+    /// Returns the events `reader_id` would get by the next `read`, without
+    /// advancing the reader.
+    ///
+    /// Calling `read` afterwards returns the same events again (plus any
+    /// events written in between).
+    pub fn peek(&self, reader_id: &ReaderId<E>) -> EventIterator<'_, E> {
+        self.storage.peek(reader_id)
+    }
+
+    /// Read any events that have been written to storage since the last read
+    /// with `reader_id`, like `read` does.
+    ///
+    /// Where `read` panics, this returns an error instead:
+    /// `ReadError::ForeignChannel` if `reader_id` was registered with another
+    /// channel and `ReadError::Unregistered` if it's not registered anymore.
+    ///
+    /// In a bounded channel, a reader which didn't keep up can have unread
+    /// events overwritten. Instead of silently skipping those, this returns
+    /// `ReadError::Lagged` with the number of missed events once. The reader
+    /// then continues at the oldest event still retained by the channel.
+    pub fn try_read(&self, reader_id: &mut ReaderId<E>) -> Result<EventIterator<'_, E>, ReadError> {
+        self.storage.try_read(reader_id)
+    }
+}
+
+impl<E> Extend<E> for EventChannel<E>
+where
+    E: Event,
+{
+    /// Writes the events of `iter` with a single `iter_write`.
+    fn extend<I>(&mut self, iter: I)
+    where
+        I: IntoIterator<Item = E>,
+    {
+        self.iter_write(iter);
+    }
+}
+
+impl<E> FromIterator<E> for EventChannel<E>
+where
+    E: Event,
+{
+    /// Creates a channel storing the events of `iter`.
+    ///
+    /// Since a new channel has no readers, the events can only be read by
+    /// readers registered with `register_reader_from_oldest` (or
+    /// `register_reader_with_history`).
+    fn from_iter<I>(iter: I) -> Self
+    where
+        I: IntoIterator<Item = E>,
+    {
+        let events = iter.into_iter().collect::<Vec<_>>();
+        let mut channel = EventChannel::with_capacity(events.len().max(DEFAULT_CAPACITY));
+        channel.iter_write(events);
+
+        channel
+    }
+}
+
+/// An `EventChannel` storing its events behind an `Arc`.
+///
+/// Reading with `read_shared` yields `Arc<E>` handles, which can be kept
+/// around after the read (e.g. sent to another thread) without cloning the
+/// event itself. The channel can still overwrite the slot; the event is freed
+/// once the last handle is dropped.
+pub type SharedEventChannel<E> = EventChannel<Arc<E>>;
+
+/// Iterator returned by `SharedEventChannel::read_shared`.
+pub type SharedEventIterator<'a, E> = Cloned<EventIterator<'a, Arc<E>>>;
+
+impl<E> EventChannel<Arc<E>>
+where
+    E: Event,
+{
+    /// Write a single event into storage, wrapping it in an `Arc`.
+    ///
+    /// Returns the sequence number assigned to the event.
+    pub fn single_write_shared(&mut self, event: E) -> u64 {
+        self.single_write(Arc::new(event))
+    }
+
+    /// Write an iterator of events into storage, wrapping each of them in an
+    /// `Arc`.
+    ///
+    /// Returns the range of sequence numbers assigned to the events.
+    pub fn iter_write_shared<I>(&mut self, iter: I) -> Range<u64>
+    where
+        I: IntoIterator<Item = E>,
+    {
+        self.iter_write(iter.into_iter().map(Arc::new))
+    }
+
+    /// Read any events that have been written to storage since the last read
+    /// with `reader_id`, like `read` does, but yield owned handles to them.
+    ///
+    /// Only the reference count is increased; the handles stay valid after
+    /// the channel has been written to again.
+    ///
+    /// ```
+    /// use std::thread;
+    ///
+    /// use shrev::SharedEventChannel;
+    ///
+    /// let mut channel = SharedEventChannel::new();
+    /// let mut reader = channel.register_reader();
+    /// channel.single_write_shared(vec![0u8; 1024]);
+    ///
+    /// let events: Vec<_> = channel.read_shared(&mut reader).collect();
+    /// channel.iter_write_shared((0..100).map(|_| vec![]));
+    ///
+    /// let len = thread::spawn(move || events[0].len()).join().unwrap();
+    /// assert_eq!(len, 1024);
+    /// ```
+    pub fn read_shared(&self, reader_id: &mut ReaderId<Arc<E>>) -> SharedEventIterator<'_, E> {
+        self.read(reader_id).cloned()
+    }
 }
 
 #[cfg(test)]
@@ -192,6 +1034,36 @@ mod tests {
         pub id: u32,
     }
 
+    #[test]
+    fn test_read_shared() {
+        let mut channel = SharedEventChannel::with_capacity(2);
+        let mut reader_id = channel.register_reader();
+        channel.iter_write_shared(vec![Test { id: 1 }, Test { id: 2 }]);
+
+        let events = channel.read_shared(&mut reader_id).collect::<Vec<_>>();
+        assert_eq!(Arc::strong_count(&events[0]), 2);
+
+        // Overwriting the slots releases the channel's handles
+        channel.iter_write_shared(vec![Test { id: 3 }, Test { id: 4 }]);
+        assert_eq!(Arc::strong_count(&events[0]), 1);
+        assert_eq!(*events[1], Test { id: 2 });
+    }
+
+    #[test]
+    fn test_extend_from_iter() {
+        let mut channel = (0..100).collect::<EventChannel<_>>();
+        let mut reader_id = channel.register_reader_from_oldest();
+        channel.extend((100..110).filter(|i| i % 2 == 0));
+
+        let data = channel.read(&mut reader_id).cloned().collect::<Vec<_>>();
+        assert_eq!(
+            data,
+            (0..110)
+                .filter(|&i| i < 100 || i % 2 == 0)
+                .collect::<Vec<_>>()
+        );
+    }
+
     #[test]
     fn test_grow() {
         let mut channel = EventChannel::with_capacity(10);
@@ -297,6 +1169,66 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_bounded_lagged() {
+        let mut channel = EventChannel::with_capacity_bounded(4, 8);
+
+        let mut slow = channel.register_reader();
+        let mut fast = channel.register_reader();
+
+        for i in 0..6 {
+            channel.single_write(i);
+            assert_eq!(channel.try_read(&mut fast).unwrap().len(), 1);
+        }
+
+        channel.iter_write(6..12);
+
+        assert_eq!(
+            channel.try_read(&mut slow).unwrap_err(),
+            ReadError::Lagged { missed: 4 }
+        );
+        assert_eq!(
+            channel
+                .try_read(&mut slow)
+                .unwrap()
+                .cloned()
+                .collect::<Vec<_>>(),
+            (4..12).collect::<Vec<_>>()
+        );
+        assert_eq!(
+            channel
+                .try_read(&mut fast)
+                .unwrap()
+                .cloned()
+                .collect::<Vec<_>>(),
+            (6..12).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_read_foreign_reader() {
+        let mut channel = EventChannel::<i32>::new();
+        let mut other = EventChannel::<i32>::new();
+        let mut reader_id = other.register_reader();
+
+        assert_eq!(
+            channel.try_read(&mut reader_id).unwrap_err(),
+            ReadError::ForeignChannel
+        );
+        assert!(other.try_read(&mut reader_id).is_ok());
+        channel.single_write(1);
+    }
+
+    #[test]
+    #[should_panic(expected = "`ReaderId` was not allocated by this `EventChannel`")]
+    fn test_read_foreign_reader_panics() {
+        let channel = EventChannel::<i32>::new();
+        let mut other = EventChannel::<i32>::new();
+        let mut reader_id = other.register_reader();
+
+        channel.read(&mut reader_id);
+    }
+
     #[derive(Clone, Debug, PartialEq, Eq)]
     pub struct TestEvent {
         data: u32,
@@ -2,16 +2,28 @@
 
 use std::{
     cell::UnsafeCell,
+    collections::VecDeque,
     fmt,
     marker::PhantomData,
+    mem,
     num::Wrapping,
-    ops::{Add, AddAssign, Sub, SubAssign},
+    ops::{Add, AddAssign, Range, Sub, SubAssign},
+    panic::{RefUnwindSafe, UnwindSafe},
     ptr,
     sync::mpsc::{self, Receiver, Sender},
+    time::Duration,
 };
 
-use crate::util::{InstanceId, NoSharedAccess, Reference};
-use std::fmt::Debug;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    clock::Clock,
+    error::{Full, ReadError, SeekError},
+    growth::{Doubling, FixedMax, GrowthPolicy},
+    util::{InstanceId, NoSharedAccess, Reference},
+};
+use std::{cmp, fmt::Debug};
 
 #[derive(Clone, Copy, Debug)]
 struct CircularIndex {
@@ -31,17 +43,8 @@ impl CircularIndex {
         }
     }
 
-    /// A magic value (!0).
-    /// This value gets set when calling `step` and we reached
-    /// the end.
-    fn magic(size: usize) -> Self {
-        CircularIndex::new(!0, size)
-    }
-
-    fn is_magic(&self) -> bool {
-        self.index == !0
-    }
-
+    /// Returns the current index and advances, until `inclusive_end` has been
+    /// returned. Afterwards, the index is set to a magic value (!0).
     fn step(&mut self, inclusive_end: usize) -> Option<usize> {
         match self.index {
             x if x == !0 => None,
@@ -114,23 +117,41 @@ impl<T> Data<T> {
         self.data.get_unchecked(index)
     }
 
-    unsafe fn put(&mut self, cursor: usize, elem: T) {
+    /// Stores `elem` under `cursor`, returning the element it replaces.
+    ///
+    /// The replaced element isn't dropped here, since its `Drop` might panic.
+    unsafe fn put(&mut self, cursor: usize, elem: T) -> Option<T> {
+        let slot = self.data.get_unchecked_mut(cursor) as *mut T;
         if self.uninitialized > 0 {
             // There is no element stored under `cursor`
             // -> do not drop anything!
-            ptr::write(self.data.get_unchecked_mut(cursor) as *mut T, elem);
+            ptr::write(slot, elem);
             self.uninitialized -= 1;
+
+            None
         } else {
-            // We can safely drop this, it's initialized.
-            *self.data.get_unchecked_mut(cursor) = elem;
+            Some(ptr::replace(slot, elem))
+        }
+    }
+
+    /// Moves the element stored under `cursor` out of the buffer, if there
+    /// is one.
+    ///
+    /// `cursor` has to be the position right after the last written element.
+    unsafe fn take_next(&mut self, cursor: usize) -> Option<T> {
+        if self.uninitialized > 0 {
+            return None;
         }
+
+        // The next slot is the only uninitialized one now.
+        self.uninitialized = 1;
+
+        Some(ptr::read(self.data.get_unchecked(cursor)))
     }
 
     /// `cursor` is the first position that gets moved to the back,
     /// free memory will be created between `cursor - 1` and `cursor`.
     unsafe fn grow(&mut self, cursor: usize, by: usize) {
-        assert!(by >= self.data.len());
-
         // Calculate how many elements we need to move
         let to_move = self.data.len() - cursor;
 
@@ -140,29 +161,81 @@ impl<T> Data<T> {
         self.data.set_len(new);
 
         // Move the elements after the cursor to the end of the buffer.
-        // Since we grew the buffer at least by the old length,
-        // the elements are non-overlapping.
-        let src = self.data.as_ptr().offset(cursor as isize);
-        let dst = self.data.as_mut_ptr().offset((cursor + by) as isize);
-        ptr::copy_nonoverlapping(src, dst, to_move);
+        // A bounded buffer might grow by less than its old length,
+        // so the elements can overlap.
+        let src = self.data.as_ptr().add(cursor);
+        let dst = self.data.as_mut_ptr().add(cursor + by);
+        ptr::copy(src, dst, to_move);
 
         self.uninitialized += by;
     }
 
+    /// The elements in `range` have to be initialized.
+    unsafe fn slice(&self, range: Range<usize>) -> &[T] {
+        self.data.get_unchecked(range)
+    }
+
+    /// Moves the element at `index` out of the buffer.
+    ///
+    /// The slot has to be marked as uninitialized already.
+    unsafe fn take(&mut self, index: usize) -> T {
+        ptr::read(self.data.get_unchecked(index))
+    }
+
+    /// Marks all elements as uninitialized, dropping all of them except the
+    /// `keep` elements up to `last`, which have to be moved out (or dropped)
+    /// by the caller.
+    unsafe fn release(&mut self, last: CircularIndex, keep: usize) {
+        let initialized = self.num_initialized();
+        // Update the bookkeeping first, so a panicking `drop` only leaks.
+        self.uninitialized = self.data.len();
+
+        for i in keep..initialized {
+            ptr::drop_in_place(self.data.get_unchecked_mut(last - i) as *mut T);
+        }
+    }
+
+    /// Moves the `keep` elements up to `last` to the start of a new buffer of
+    /// `size` elements.
+    ///
+    /// All other elements are dropped with the returned `Released`, which
+    /// should happen once the ring buffer is consistent again.
+    unsafe fn shrink(&mut self, last: CircularIndex, keep: usize, size: usize) -> Released<T> {
+        let initialized = self.num_initialized();
+        let mut data = Vec::<T>::with_capacity(size);
+        for i in 0..keep {
+            let src = self.data.as_ptr().add(last - (keep - 1 - i));
+            ptr::copy_nonoverlapping(src, data.as_mut_ptr().add(i), 1);
+        }
+        data.set_len(size);
+
+        let mut old = mem::replace(&mut self.data, data);
+        self.uninitialized = size - keep;
+        old.set_len(0);
+
+        Released {
+            data: old,
+            last,
+            range: keep..initialized,
+        }
+    }
+
     /// Called when dropping the ring buffer.
     unsafe fn clean(&mut self, cursor: usize) {
         let mut cursor = CircularIndex::new(cursor, self.data.len());
         let end = cursor - 1;
+        // If dropping an element panics, the remaining ones are leaked
+        // instead of being dropped by the `Vec`.
+        self.data.set_len(0);
+        let data = self.data.as_mut_ptr();
 
         while let Some(i) = cursor.step(end) {
             if self.uninitialized > 0 {
                 self.uninitialized -= 1;
             } else {
-                ptr::drop_in_place(self.data.get_unchecked_mut(i) as *mut T);
+                ptr::drop_in_place(data.add(i));
             }
         }
-
-        self.data.set_len(0);
     }
 
     fn num_initialized(&self) -> usize {
@@ -170,6 +243,28 @@ impl<T> Data<T> {
     }
 }
 
+/// The elements of a buffer replaced by `Data::shrink`, which get dropped
+/// together with this.
+struct Released<T> {
+    /// The old buffer, with a length of zero
+    data: Vec<T>,
+    last: CircularIndex,
+    /// Offsets before `last` of the elements to drop
+    range: Range<usize>,
+}
+
+impl<T> Drop for Released<T> {
+    fn drop(&mut self) {
+        // If dropping an element panics, the remaining ones are leaked
+        // instead of being dropped by the `Vec`.
+        for i in self.range.clone() {
+            unsafe {
+                ptr::drop_in_place(self.data.as_mut_ptr().add(self.last - i));
+            }
+        }
+    }
+}
+
 impl<T: Debug> Debug for Data<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         f.debug_struct("Data")
@@ -181,8 +276,17 @@ impl<T: Debug> Debug for Data<T> {
 
 #[derive(Copy, Clone, Debug)]
 struct Reader {
+    /// Incremented whenever the reader gets removed, so stale `ReaderId`s of
+    /// a reused slot can be told apart.
+    epoch: usize,
     generation: usize,
     last_index: usize,
+    /// Generation of the last access through a `ReaderId`.
+    last_read: usize,
+    /// Number of unread events which got overwritten since the last read.
+    lagged: usize,
+    /// Sequence number of the next unread element when the reader got paused.
+    paused_at: Option<u64>,
 }
 
 impl Reader {
@@ -194,6 +298,10 @@ impl Reader {
         self.last_index != !0
     }
 
+    fn paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
     fn distance_from(&self, last: CircularIndex, current_gen: usize) -> usize {
         let this = CircularIndex {
             index: self.last_index,
@@ -206,6 +314,10 @@ impl Reader {
         }
     }
 
+    fn pending(&self, last: CircularIndex, current_gen: usize) -> usize {
+        last.size - self.distance_from(last, current_gen)
+    }
+
     fn needs_shift(&self, last_index: usize, current_gen: usize) -> bool {
         self.last_index > last_index
             || (self.last_index == last_index && self.generation != current_gen)
@@ -223,18 +335,21 @@ impl Reader {
 ///
 /// Note that as long as a `ReaderId` exists, it is crucial to use it to read
 /// the events; otherwise the buffer of the `EventChannel` **will** keep
-/// growing.
+/// growing, unless the reader expires (see
+/// `EventChannel::set_reader_expiry`).
 pub struct ReaderId<T: 'static> {
+    epoch: usize,
     id: usize,
     marker: PhantomData<&'static [T]>,
     reference: Reference,
     // stupid way to make this `Sync`
-    drop_notifier: NoSharedAccess<Sender<usize>>,
+    drop_notifier: NoSharedAccess<Sender<(usize, usize)>>,
 }
 
 impl<T: 'static> fmt::Debug for ReaderId<T> {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("ReaderId")
+            .field("epoch", &self.epoch)
             .field("id", &self.id)
             .field("marker", &self.marker)
             .field("reference", &self.reference)
@@ -245,51 +360,145 @@ impl<T: 'static> fmt::Debug for ReaderId<T> {
 
 impl<T: 'static> Drop for ReaderId<T> {
     fn drop(&mut self) {
-        let _ = self.drop_notifier.get_mut().send(self.id);
+        let _ = self.drop_notifier.get_mut().send((self.id, self.epoch));
+    }
+}
+
+/// A snapshot of the position of a `ReaderId`.
+///
+/// It's obtained with `EventChannel::cursor` and can be used to move a reader
+/// of the same channel back to that position with `EventChannel::restore`, as
+/// long as the events after it haven't been overwritten.
+///
+/// A cursor only belongs to the channel it was taken from. Deserializing it in
+/// another process (e.g. after a restart) works, but restoring it is rejected
+/// by every channel there, since they're different channels.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ReaderCursor {
+    channel: u64,
+    sequence: u64,
+}
+
+impl ReaderCursor {
+    /// Returns the sequence number of the event the reader would read next.
+    pub fn sequence(&self) -> u64 {
+        self.sequence
+    }
+}
+
+/// A reader ID which only receives the events accepted by its filter.
+///
+/// It's created by `EventChannel::register_filtered_reader` and used with
+/// `EventChannel::read_filtered`. Just like a `ReaderId`, it needs to be used
+/// for reading (or dropped), otherwise the buffer keeps growing.
+#[derive(Debug)]
+pub struct FilteredReaderId<T: 'static> {
+    reader_id: ReaderId<T>,
+}
+
+impl<T: 'static> FilteredReaderId<T> {
+    /// Returns the underlying reader id, e.g. to pass it to
+    /// `EventChannel::pending`.
+    pub fn reader_id(&self) -> &ReaderId<T> {
+        &self.reader_id
+    }
+
+    /// Returns the underlying reader id mutably, e.g. to pass it to
+    /// `EventChannel::pause` or `EventChannel::seek`.
+    ///
+    /// Reading with it directly ignores the filter.
+    pub fn reader_id_mut(&mut self) -> &mut ReaderId<T> {
+        &mut self.reader_id
     }
 }
 
-#[derive(Default)]
-struct ReaderMeta {
+/// Decides which events a filtered reader receives.
+type Filter<T> = Box<dyn Fn(&T) -> bool + Send + Sync + UnwindSafe + RefUnwindSafe>;
+
+struct ReaderMeta<T> {
+    /// Number of active readers which aren't paused
+    active: usize,
+    /// Filters of the readers, if any
+    filters: Vec<Option<Filter<T>>>,
     /// Free ids
     free: Vec<usize>,
+    /// Ids of paused readers
+    paused: Vec<usize>,
     readers: Vec<UnsafeCell<Reader>>,
 }
 
-impl ReaderMeta {
+impl<T> ReaderMeta<T> {
     fn new() -> Self {
-        Default::default()
+        ReaderMeta {
+            active: 0,
+            filters: Vec::new(),
+            free: Vec::new(),
+            paused: Vec::new(),
+            readers: Vec::new(),
+        }
     }
 
-    fn reader<T>(&self, id: &mut ReaderId<T>) -> Option<&mut Reader> {
+    #[allow(clippy::mut_from_ref)]
+    fn reader(&self, id: &mut ReaderId<T>) -> Option<&mut Reader> {
         self.readers.get(id.id).map(|r| unsafe { &mut *r.get() })
     }
 
+    fn reader_shared(&self, id: &ReaderId<T>) -> Option<&Reader> {
+        self.readers.get(id.id).map(|r| unsafe { &*r.get() })
+    }
+
+    fn filter(&self, id: usize) -> Option<&Filter<T>> {
+        self.filters[id].as_ref()
+    }
+
+    fn set_filter(&mut self, id: usize, filter: Filter<T>) {
+        self.filters[id] = Some(filter);
+    }
+
     fn reader_exclusive(&mut self, id: usize) -> &mut Reader {
         unsafe { &mut *self.readers[id].get() }
     }
 
-    fn has_reader(&mut self) -> bool {
+    // Only looks at `active`, since other readers might be accessed concurrently.
+    fn has_reader(&self) -> bool {
+        self.active > 0
+    }
+
+    fn has_reader_accepting(&mut self, elem: &T) -> bool {
         self.readers
             .iter()
-            .map(|r| unsafe { &mut *r.get() })
-            .any(|r| r.active())
+            .zip(&self.filters)
+            .map(|(reader, filter)| (unsafe { &*reader.get() }, filter))
+            .filter(|(reader, _)| reader.active() && !reader.paused())
+            .any(|(_, filter)| filter.as_ref().is_none_or(|filter| filter(elem)))
     }
 
     fn alloc(&mut self, last_index: usize, generation: usize) -> usize {
+        self.active += 1;
+
         match self.free.pop() {
             Some(id) => {
-                self.reader_exclusive(id).last_index = last_index;
-                self.reader_exclusive(id).generation = generation;
+                let reader = self.reader_exclusive(id);
+                reader.last_index = last_index;
+                reader.generation = generation;
+                reader.last_read = generation;
+                reader.lagged = 0;
+                reader.paused_at = None;
 
                 id
             }
             None => {
                 let id = self.readers.len();
                 self.readers.push(UnsafeCell::new(Reader {
+                    epoch: 0,
                     generation,
                     last_index,
+                    last_read: generation,
+                    lagged: 0,
+                    paused_at: None,
                 }));
+                self.filters.push(None);
 
                 id
             }
@@ -297,16 +506,96 @@ impl ReaderMeta {
     }
 
     fn remove(&mut self, id: usize) {
-        self.reader_exclusive(id).set_inactive();
+        if self.reader_exclusive(id).paused() {
+            self.paused.retain(|&paused| paused != id);
+        } else {
+            self.active -= 1;
+        }
+        let reader = self.reader_exclusive(id);
+        reader.set_inactive();
+        reader.epoch = reader.epoch.wrapping_add(1);
+        self.filters[id] = None;
         self.free.push(id);
     }
 
+    /// Removes the reader `id` if `epoch` is still its current epoch.
+    fn remove_epoch(&mut self, id: usize, epoch: usize) {
+        if self.reader_exclusive(id).epoch == epoch {
+            self.remove(id);
+        }
+    }
+
+    /// Removes every reader which hasn't been accessed for more than `limit`
+    /// generations, not counting paused readers.
+    fn expire_idle(&mut self, current_gen: usize, limit: usize) {
+        for id in 0..self.readers.len() {
+            let reader = self.reader_exclusive(id);
+            let idle = (Wrapping(current_gen) - Wrapping(reader.last_read)).0;
+            if reader.active() && !reader.paused() && idle > limit {
+                self.remove(id);
+            }
+        }
+    }
+
+    /// Returns the largest number of unread elements of any reader which isn't
+    /// paused.
+    fn max_pending(&mut self, last: CircularIndex, current_gen: usize) -> usize {
+        self.readers
+            .iter()
+            .map(|reader| unsafe { &*reader.get() })
+            .filter(|reader| reader.active() && !reader.paused())
+            .map(|reader| reader.pending(last, current_gen))
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Moves the readers from `old_last` to `new_last`, keeping the number of
+    /// elements they didn't read yet, up to the `keep` elements which are
+    /// left. Paused readers end up at `new_last`.
+    fn reposition(
+        &mut self,
+        old_last: CircularIndex,
+        new_last: CircularIndex,
+        current_gen: usize,
+        keep: usize,
+    ) {
+        for reader in &mut self.readers {
+            let reader = unsafe { &mut *reader.get() } as &mut Reader;
+            if !reader.active() {
+                continue;
+            }
+
+            let pending = if reader.paused() {
+                0
+            } else {
+                cmp::min(reader.pending(old_last, current_gen), keep)
+            };
+            reader.last_index = new_last - pending;
+            reader.generation = match pending {
+                0 => current_gen,
+                // Any other generation marks the elements as unread
+                _ => current_gen.wrapping_sub(1),
+            };
+        }
+    }
+
+    /// Checks if any reader other than `id` has unread elements.
+    fn others_pending(&mut self, id: usize, last: CircularIndex, current_gen: usize) -> bool {
+        self.readers
+            .iter()
+            .enumerate()
+            .filter(|&(other, _)| other != id)
+            .map(|(_, reader)| unsafe { &*reader.get() })
+            .filter(|reader| reader.active() && !reader.paused())
+            .any(|reader| reader.pending(last, current_gen) > 0)
+    }
+
     // This needs to be mutable since `readers` might be borrowed in `reader`!
     fn nearest_index(&mut self, last: CircularIndex, current_gen: usize) -> Option<&Reader> {
         self.readers
             .iter()
             .map(|reader| unsafe { &*reader.get() })
-            .filter(|reader| reader.active())
+            .filter(|reader| reader.active() && !reader.paused())
             .min_by_key(|reader| reader.distance_from(last, current_gen))
     }
 
@@ -322,27 +611,125 @@ impl ReaderMeta {
             }
         }
     }
+
+    /// Pauses the reader `id`, whose next unread element has the sequence
+    /// number `seq`.
+    fn pause(&mut self, id: usize, seq: u64) {
+        let reader = self.reader_exclusive(id);
+        if reader.paused() {
+            return;
+        }
+
+        reader.paused_at = Some(seq);
+        self.paused.push(id);
+        self.active -= 1;
+    }
+
+    /// Resumes the reader `id`, returning the sequence number at which it was
+    /// paused.
+    fn resume(&mut self, id: usize) -> Option<u64> {
+        let paused_at = self.reader_exclusive(id).paused_at.take()?;
+        self.paused.retain(|&paused| paused != id);
+        self.active += 1;
+
+        Some(paused_at)
+    }
+
+    /// Moves all paused readers to `last_index`, so they never hold back
+    /// writes and never observe any elements.
+    fn catch_up_paused(&mut self, last_index: usize, current_gen: usize) {
+        for &id in &self.paused {
+            let reader = unsafe { &mut *self.readers[id].get() };
+            reader.last_index = last_index;
+            reader.generation = current_gen;
+        }
+    }
+
+    /// Moves every reader which would lose unread events by writing `num`
+    /// elements after `last` to the oldest event retained after that write.
+    fn skip_overwritten(&mut self, last: CircularIndex, current_gen: usize, num: usize) {
+        for reader in &mut self.readers {
+            let reader = unsafe { &mut *reader.get() } as &mut Reader;
+            if !reader.active() || reader.paused() {
+                continue;
+            }
+
+            let free = reader.distance_from(last, current_gen);
+            if free < num {
+                reader.lagged += num - free;
+                // Any other generation marks the whole buffer as unread, no
+                // matter when the write changes the generation.
+                reader.last_index = last + num;
+                reader.generation = current_gen.wrapping_sub(1);
+            }
+        }
+    }
 }
 
-unsafe impl Send for ReaderMeta {}
-unsafe impl Sync for ReaderMeta {}
+unsafe impl<T> Send for ReaderMeta<T> {}
+unsafe impl<T> Sync for ReaderMeta<T> {}
+
+/// The elements written by a single write.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Batch {
+    /// Sequence number of the first element
+    start: u64,
+    /// Time of the write, if a clock is set
+    time: Duration,
+}
 
 /// Ring buffer, holding data of type `T`.
 pub struct RingBuffer<T> {
     available: usize,
+    /// The writes, starting with the write of the oldest element still stored
+    batches: VecDeque<Batch>,
+    clock: Option<Box<dyn Clock>>,
     last_index: CircularIndex,
     data: Data<T>,
-    free_rx: NoSharedAccess<Receiver<usize>>,
-    free_tx: NoSharedAccess<Sender<usize>>,
+    /// Number of writes after which readers which didn't read expire
+    expiry: Option<usize>,
+    free_rx: NoSharedAccess<Receiver<(usize, usize)>>,
+    free_tx: NoSharedAccess<Sender<(usize, usize)>>,
     generation: Wrapping<usize>,
+    growth: Box<dyn GrowthPolicy>,
     instance_id: InstanceId,
-    meta: ReaderMeta,
+    max_size: Option<usize>,
+    meta: ReaderMeta<T>,
+    /// Whether the current write overwrites unread elements, so the readers
+    /// have to be moved before writing each element
+    overwriting: bool,
+    /// Whether to shrink back to `initial_size` once all readers caught up
+    auto_shrink: bool,
+    initial_size: usize,
+    /// Number of elements which are kept even if no reader needs them
+    retention: usize,
+    /// Sequence number of the next element
+    written: u64,
 }
 
 impl<T: 'static> RingBuffer<T> {
-    /// Create a new ring buffer with the given max size.
+    /// Create a new ring buffer with the given initial size.
     pub fn new(size: usize) -> Self {
+        Self::with_growth_policy(size, Box::new(Doubling))
+    }
+
+    /// Create a new ring buffer with the given initial size, which never
+    /// grows beyond `max_size`.
+    ///
+    /// Once `max_size` is reached, writes overwrite the oldest events, even
+    /// if not every reader has observed them yet.
+    pub fn new_bounded(size: usize, max_size: usize) -> Self {
+        Self::with_growth_policy(size, Box::new(FixedMax::new(max_size)))
+    }
+
+    /// Create a new ring buffer with the given initial size, which grows as
+    /// decided by `growth`.
+    pub fn with_growth_policy(size: usize, growth: Box<dyn GrowthPolicy>) -> Self {
         assert!(size > 1);
+        let max_size = growth.max_capacity();
+        if let Some(max_size) = max_size {
+            assert!(max_size >= size);
+        }
 
         let (free_tx, free_rx) = mpsc::channel();
         let free_tx = NoSharedAccess::new(free_tx);
@@ -350,40 +737,294 @@ impl<T: 'static> RingBuffer<T> {
 
         RingBuffer {
             available: size,
+            batches: VecDeque::new(),
+            clock: None,
             last_index: CircularIndex::at_end(size),
             data: Data::new(size),
+            expiry: None,
             free_rx,
             free_tx,
             generation: Wrapping(0),
+            growth,
             instance_id: InstanceId::new("`ReaderId` was not allocated by this `EventChannel`"),
+            max_size,
             meta: ReaderMeta::new(),
+            overwriting: false,
+            auto_shrink: false,
+            initial_size: size,
+            retention: 0,
+            written: 0,
         }
     }
 
     /// Iterates over all elements of `iter` and pushes them to the buffer.
-    pub fn iter_write<I>(&mut self, iter: I)
+    ///
+    /// If the size hint of `iter` is exact, the space is reserved upfront;
+    /// otherwise, the buffer grows while writing. Since the size hint might
+    /// be wrong, additional elements are still written.
+    ///
+    /// Returns the sequence numbers assigned to the elements.
+    pub fn iter_write<I>(&mut self, iter: I) -> Range<u64>
     where
         I: IntoIterator<Item = T>,
-        I::IntoIter: ExactSizeIterator,
     {
+        self.maybe_shrink();
+        let start = self.written;
         let iter = iter.into_iter();
+        let reserved = match iter.size_hint() {
+            (len, Some(upper)) if len == upper && len > 0 => {
+                self.expire_idle_readers();
+                self.ensure_additional(len);
+
+                len
+            }
+            _ => 0,
+        };
+        self.write_iter(iter, reserved);
+
+        start..self.written
+    }
+
+    /// Pushes all elements of `iter` to the buffer like `iter_write`, but
+    /// only if that doesn't overwrite any unread events.
+    ///
+    /// Returns the untouched iterator otherwise.
+    ///
+    /// Panics if `iter` yields more elements than its `len`, after writing
+    /// the first `len` ones.
+    pub fn try_iter_write<I>(&mut self, iter: I) -> Result<Range<u64>, Full<I::IntoIter>>
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        self.maybe_shrink();
+        let start = self.written;
+        let mut iter = iter.into_iter();
         let len = iter.len();
         if len > 0 {
-            self.ensure_additional(len);
-            for element in iter {
-                unsafe {
-                    self.data.put(self.last_index + 1, element);
-                }
-                self.last_index += 1;
+            self.expire_idle_readers();
+            if self.available < len && !self.reserve(len) {
+                return Err(Full(iter));
+            }
+            self.write_iter(iter.by_ref().take(len), len);
+            assert!(
+                iter.next().is_none(),
+                "ExactSizeIterator yielded more elements than its length"
+            );
+        }
+
+        Ok(start..self.written)
+    }
+
+    /// Writes the elements of `iter`, where space has been made for the first
+    /// `reserved` ones already. For any further element, room is made right
+    /// before writing it.
+    fn write_iter<I>(&mut self, iter: I, reserved: usize)
+    where
+        I: Iterator<Item = T>,
+    {
+        let (start, time) = (self.written, self.now());
+        // Finishes the write even if `iter` panics.
+        let write = PendingWrite {
+            buffer: self,
+            start,
+            time,
+        };
+        for element in iter {
+            write.buffer.push(element, start, reserved);
+        }
+    }
+
+    /// Writes a single element of the write which started at `start`.
+    ///
+    /// The buffer is consistent afterwards, even if dropping the overwritten
+    /// element panics.
+    fn push(&mut self, element: T, start: u64, reserved: usize) {
+        let count = (self.written - start) as usize;
+        if count >= reserved {
+            if count == 0 {
+                self.expire_idle_readers();
+            }
+            self.ensure_additional(1);
+        }
+        self.skip_overwritten();
+        let replaced = unsafe { self.data.put(self.last_index + 1, element) };
+        self.last_index += 1;
+        self.available -= 1;
+        self.written += 1;
+        if self.written == start + 1 {
+            // From now on, readers need to see unread elements when
+            // making room for another one.
+            self.generation += Wrapping(1);
+        }
+
+        drop(replaced);
+    }
+
+    /// Writes `num` elements created by `f`, which gets the element it
+    /// overwrites (if any) to reuse it.
+    ///
+    /// Returns the sequence numbers assigned to the elements.
+    pub fn write_with<F>(&mut self, num: usize, mut f: F) -> Range<u64>
+    where
+        F: FnMut(Option<T>) -> T,
+    {
+        self.maybe_shrink();
+        let start = self.written;
+        if num > 0 {
+            self.expire_idle_readers();
+            self.ensure_additional(num);
+
+            let time = self.now();
+            let write = PendingWrite {
+                buffer: self,
+                start,
+                time,
+            };
+            for _ in 0..num {
+                write.buffer.push_with(&mut f, start);
             }
-            self.available -= len;
+        }
+
+        start..self.written
+    }
+
+    /// Writes a single element created by `f`, which gets the element it
+    /// overwrites (if any) to reuse it.
+    pub fn single_write_with<F>(&mut self, f: F) -> u64
+    where
+        F: FnOnce(Option<T>) -> T,
+    {
+        let mut f = Some(f);
+
+        self.write_with(1, |old| f.take().unwrap()(old)).start
+    }
+
+    /// Writes an element created by `f` in the space reserved for the write
+    /// which started at `start`.
+    fn push_with<F>(&mut self, f: F, start: u64)
+    where
+        F: FnOnce(Option<T>) -> T,
+    {
+        self.skip_overwritten();
+        let cursor = self.last_index + 1;
+        // If `f` panics, the slot is simply left uninitialized.
+        let old = unsafe { self.data.take_next(cursor) };
+        let element = f(old);
+        unsafe {
+            self.data.put(cursor, element);
+        }
+        self.last_index += 1;
+        self.available -= 1;
+        self.written += 1;
+        if self.written == start + 1 {
             self.generation += Wrapping(1);
         }
     }
 
+    /// Moves the readers which would lose an unread element by writing the
+    /// next one, if the current write overwrites unread elements.
+    #[inline(always)]
+    fn skip_overwritten(&mut self) {
+        if self.overwriting {
+            self.meta
+                .skip_overwritten(self.last_index, self.generation.0, 1);
+        }
+    }
+
+    /// Records the write of the elements starting at `start`.
+    fn finish_write(&mut self, start: u64, time: Duration) {
+        self.batches.push_back(Batch { start, time });
+        self.prune_batches();
+        self.meta
+            .catch_up_paused(self.last_index.index, self.generation.0);
+    }
+
+    /// Forgets the writes whose elements are all gone.
+    fn prune_batches(&mut self) {
+        let oldest = self.oldest_retained();
+        while self.batches.get(1).is_some_and(|next| next.start <= oldest) {
+            self.batches.pop_front();
+        }
+    }
+
+    /// Returns the number of elements the buffer can hold.
+    pub fn capacity(&self) -> usize {
+        self.last_index.size
+    }
+
+    /// Shrinks the buffer as much as possible, but not below `min_size`.
+    ///
+    /// The elements readers haven't read yet and retained elements are kept.
+    pub fn shrink_to(&mut self, min_size: usize) {
+        self.maintain();
+        let (last, current_gen) = (self.last_index, self.generation.0);
+        let retained = cmp::min(self.retention, self.data.num_initialized());
+        let keep = cmp::max(self.meta.max_pending(last, current_gen), retained);
+        let size = cmp::max(cmp::max(min_size, keep), 2);
+        if size >= last.size {
+            return;
+        }
+
+        let released = unsafe { self.data.shrink(last, keep, size) };
+        self.last_index = match keep {
+            0 => CircularIndex::at_end(size),
+            _ => CircularIndex::new(keep - 1, size),
+        };
+        self.meta
+            .reposition(last, self.last_index, current_gen, keep);
+        // The next write has to check the readers again.
+        self.available = 0;
+        self.prune_batches();
+
+        // Dropping the elements last, since their `Drop` might panic.
+        drop(released);
+    }
+
+    /// Returns whether the buffer shrinks back to its initial size once all
+    /// readers caught up.
+    pub fn auto_shrink(&self) -> bool {
+        self.auto_shrink
+    }
+
+    /// Makes the buffer shrink back to its initial size on a write, if all
+    /// readers caught up.
+    pub fn set_auto_shrink(&mut self, auto_shrink: bool) {
+        self.auto_shrink = auto_shrink;
+    }
+
+    /// The size the buffer shrinks to automatically.
+    ///
+    /// It leaves room for one element besides the retained ones, so the next
+    /// write doesn't have to grow again.
+    fn shrink_target(&self) -> usize {
+        cmp::max(self.initial_size, self.retention + 1)
+    }
+
+    #[inline(always)]
+    fn maybe_shrink(&mut self) {
+        if self.auto_shrink && self.last_index.size > self.shrink_target() {
+            self.shrink_if_caught_up();
+        }
+    }
+
+    #[inline(never)]
+    fn shrink_if_caught_up(&mut self) {
+        self.maintain();
+        if self.meta.max_pending(self.last_index, self.generation.0) == 0 {
+            self.shrink_to(self.shrink_target());
+        }
+    }
+
+    fn now(&self) -> Duration {
+        self.clock
+            .as_ref()
+            .map_or(Duration::ZERO, |clock| clock.now())
+    }
+
     /// Removes all elements from a `Vec` and pushes them to the ring buffer.
-    pub fn drain_vec_write(&mut self, data: &mut Vec<T>) {
-        self.iter_write(data.drain(..));
+    pub fn drain_vec_write(&mut self, data: &mut Vec<T>) -> Range<u64> {
+        self.iter_write(data.drain(..))
     }
 
     // Checks if any reader would observe an additional event.
@@ -393,6 +1034,21 @@ impl<T: 'static> RingBuffer<T> {
         self.meta.has_reader()
     }
 
+    /// Checks if any reader would observe `elem` if it was written, taking
+    /// the filters of filtered readers into account.
+    pub fn would_write_elem(&mut self, elem: &T) -> bool {
+        self.maintain();
+
+        self.meta.has_reader_accepting(elem)
+    }
+
+    /// Checks if any reader might observe an additional event.
+    ///
+    /// Readers dropped since the last mutable access are still counted.
+    pub fn might_write(&self) -> bool {
+        self.meta.has_reader()
+    }
+
     /// Ensures that `num` elements can be inserted.
     /// Does nothing if there's enough space, grows the buffer otherwise.
     #[inline(always)]
@@ -406,20 +1062,50 @@ impl<T: 'static> RingBuffer<T> {
 
     #[inline(never)]
     fn ensure_additional_slow(&mut self, num: usize) {
+        if self.reserve(num) {
+            return;
+        }
+
+        // We hit the maximum size, so the slowest readers lose their oldest
+        // events. They're only moved once an element actually gets written,
+        // since the write might end early.
+        if let Some(max_size) = self.max_size {
+            self.grow_to(max_size);
+        }
+        self.overwriting = true;
+        self.available = num;
+    }
+
+    /// Tries to make room for `num` elements without overwriting any unread
+    /// events, growing the buffer if the maximum size allows it.
+    ///
+    /// Returns `false` (without growing) if the elements don't fit.
+    fn reserve(&mut self, num: usize) -> bool {
         self.maintain();
-        let left: usize = match self.meta.nearest_index(self.last_index, self.generation.0) {
+        let (last, current_gen) = (self.last_index, self.generation.0);
+        let mut left = self
+            .meta
+            .nearest_index(last, current_gen)
+            .map(|reader| reader.distance_from(last, current_gen));
+        if self.retention > 0 {
+            let retained = cmp::min(self.retention, self.data.num_initialized());
+            let free = self.last_index.size - retained;
+            left = Some(left.map_or(free, |left| cmp::min(left, free)));
+        }
+
+        let left: usize = match left {
             None => {
-                self.available = self.last_index.size;
+                // Without readers, nobody can miss an event, even if a write
+                // doesn't fit into the buffer at all.
+                self.available = cmp::max(self.last_index.size, num);
 
-                return;
+                return true;
             }
-            Some(reader) => {
-                let left = reader.distance_from(self.last_index, self.generation.0);
-
+            Some(left) => {
                 self.available = left;
 
                 if left >= num {
-                    return;
+                    return true;
                 } else {
                     left
                 }
@@ -428,14 +1114,29 @@ impl<T: 'static> RingBuffer<T> {
         let grow_by = num - left;
         let min_target_size = self.last_index.size + grow_by;
 
-        // Make sure size' = 2^n * size
-        let mut size = 2 * self.last_index.size;
-        while size < min_target_size {
-            size *= 2;
+        if self.max_size.is_some_and(|max| min_target_size > max) {
+            return false;
+        }
+
+        let size = self.growth.grow(self.last_index.size, min_target_size);
+        let mut size = cmp::max(size, min_target_size);
+        if let Some(max_size) = self.max_size {
+            size = cmp::min(size, max_size);
         }
 
+        self.grow_to(size);
+
+        true
+    }
+
+    /// Grows the buffer to `size` elements, inserting the free space right
+    /// after the last written element.
+    fn grow_to(&mut self, size: usize) {
         // Calculate adjusted growth
         let grow_by = size - self.last_index.size;
+        if grow_by == 0 {
+            return;
+        }
 
         // Insert the additional elements
         unsafe {
@@ -445,75 +1146,567 @@ impl<T: 'static> RingBuffer<T> {
 
         self.meta
             .shift(self.last_index.index, self.generation.0, grow_by);
-        self.available = grow_by + left
+        self.available += grow_by;
     }
 
     fn maintain(&mut self) {
-        while let Ok(id) = self.free_rx.get_mut().try_recv() {
-            self.meta.remove(id);
+        while let Ok((id, epoch)) = self.free_rx.get_mut().try_recv() {
+            self.meta.remove_epoch(id, epoch);
+        }
+    }
+
+    /// Removes the readers which didn't read for longer than the expiry
+    /// allows, counting the write which is about to happen.
+    fn expire_idle_readers(&mut self) {
+        if let Some(expiry) = self.expiry {
+            let next_gen = self.generation + Wrapping(1);
+            self.meta.expire_idle(next_gen.0, expiry);
         }
     }
 
     /// Write a single data point into the ring buffer.
-    pub fn single_write(&mut self, element: T) {
+    pub fn single_write(&mut self, element: T) -> u64 {
         use std::iter::once;
 
-        self.iter_write(once(element));
+        self.iter_write(once(element)).start
     }
 
-    /// Create a new reader id for this ring buffer.
-    pub fn new_reader_id(&mut self) -> ReaderId<T> {
-        self.maintain();
-        let last_index = self.last_index.index;
-        let generation = self.generation.0;
-        let id = self.meta.alloc(last_index, generation);
+    /// Write a single data point into the ring buffer, but only if that
+    /// doesn't overwrite any unread events.
+    pub fn try_single_write(&mut self, element: T) -> Result<u64, Full<T>> {
+        use std::iter::once;
 
-        ReaderId {
-            id,
-            marker: PhantomData,
-            reference: self.instance_id.reference(),
-            drop_notifier: NoSharedAccess::new(self.free_tx.get_mut().clone()),
-        }
+        self.try_iter_write(once(element))
+            .map(|range| range.start)
+            .map_err(|Full(mut iter)| Full(iter.next().unwrap()))
     }
 
-    /// Read data from the ring buffer, starting where the last read ended, and
-    /// up to where the last element was written.
-    pub fn read(&self, reader_id: &mut ReaderId<T>) -> StorageIterator<T> {
-        // Check if `reader_id` was actually created for this buffer.
-        // This is very important as `reader_id` is a token allowing memory access,
-        // and without this check a race could be caused by duplicate IDs.
-        self.instance_id.assert_eq(&reader_id.reference);
+    /// Returns the sequence number of the oldest element still stored.
+    pub fn oldest_retained(&self) -> u64 {
+        self.written - self.data.num_initialized() as u64
+    }
 
-        let (last_read_index, gen) = {
-            let reader = self.meta.reader(reader_id).unwrap_or_else(|| {
-                panic!(
-                    "ReaderId not registered: {}\n\
-                     This usually means that this ReaderId \
-                     was created by a different storage",
-                    reader_id.id
-                )
-            });
-            let old = reader.last_index;
-            reader.last_index = self.last_index.index;
-            let old_gen = reader.generation;
-            reader.generation = self.generation.0;
+    /// Moves `reader_id` so the next read starts at the element with the
+    /// sequence number `seq`.
+    pub fn seek(&mut self, reader_id: &mut ReaderId<T>, seq: u64) -> Result<(), SeekError> {
+        let oldest = self.oldest_retained();
+        if seq < oldest {
+            return Err(SeekError::Overwritten { oldest });
+        }
+        if seq > self.written {
+            return Err(SeekError::NotWritten { next: self.written });
+        }
+
+        let (last_index, generation) = self.position_before(seq);
+        let reader = self.reader(reader_id);
+        reader.lagged = 0;
+        if let Some(paused_at) = reader.paused_at.as_mut() {
+            // Nothing keeps the elements of a paused reader from being
+            // overwritten, so it only changes where it resumes.
+            *paused_at = seq;
+
+            return Ok(());
+        }
+        reader.last_index = last_index;
+        reader.generation = generation;
+        // The reader might need elements the next write would overwrite.
+        self.available = 0;
+
+        Ok(())
+    }
+
+    /// Returns the reader state (last index and generation) of a reader whose
+    /// next element has the sequence number `seq`.
+    ///
+    /// `seq` has to be in the retained range.
+    fn position_before(&self, seq: u64) -> (usize, usize) {
+        let unread = (self.written - seq) as usize;
+        let last_index = self.last_index - unread % self.last_index.size;
+        let generation = match unread {
+            0 => self.generation,
+            // Any other generation marks the elements as unread
+            _ => self.generation - Wrapping(1),
+        };
+
+        (last_index, generation.0)
+    }
 
-            (old, old_gen)
+    /// Returns a snapshot of the position of `reader_id`.
+    pub fn cursor(&self, reader_id: &ReaderId<T>) -> ReaderCursor {
+        let sequence = match self.reader_shared(reader_id).paused_at {
+            Some(paused_at) => paused_at,
+            None => self.written - self.pending(reader_id) as u64,
         };
+
+        ReaderCursor {
+            channel: self.instance_id.serial(),
+            sequence,
+        }
+    }
+
+    /// Moves `reader_id` back to the position captured by `cursor`.
+    pub fn restore(
+        &mut self,
+        reader_id: &mut ReaderId<T>,
+        cursor: ReaderCursor,
+    ) -> Result<(), SeekError> {
+        if cursor.channel != self.instance_id.serial() {
+            return Err(SeekError::ForeignCursor);
+        }
+
+        self.seek(reader_id, cursor.sequence)
+    }
+
+    /// Returns the number of elements which are kept even if no reader needs
+    /// them anymore.
+    pub fn retention(&self) -> usize {
+        self.retention
+    }
+
+    /// Makes sure the last `retention` elements are kept, even if no reader
+    /// needs them anymore.
+    pub fn set_retention(&mut self, retention: usize) {
+        if let Some(max_size) = self.max_size {
+            assert!(
+                retention < max_size,
+                "retention has to be smaller than the maximum size"
+            );
+        }
+
+        self.retention = retention;
+        // The next write has to check the retained elements.
+        self.available = 0;
+    }
+
+    /// Makes every following write record the current time of `clock`.
+    ///
+    /// Panics if anything has been written already.
+    pub fn set_clock(&mut self, clock: Box<dyn Clock>) {
+        assert_eq!(
+            self.written, 0,
+            "The clock has to be set before the first write"
+        );
+
+        self.clock = Some(clock);
+    }
+
+    /// Returns the number of writes after which a reader which didn't read
+    /// expires, if any.
+    pub fn reader_expiry(&self) -> Option<usize> {
+        self.expiry
+    }
+
+    /// Makes readers expire once they didn't read for more than `expiry`
+    /// writes, or disables expiry with `None`.
+    pub fn set_reader_expiry(&mut self, expiry: Option<usize>) {
+        self.expiry = expiry;
+    }
+
+    /// Create a new reader id for this ring buffer.
+    pub fn new_reader_id(&mut self) -> ReaderId<T> {
+        self.maintain();
+        let last_index = self.last_index.index;
+        let generation = self.generation.0;
+
+        self.alloc_reader_id(last_index, generation)
+    }
+
+    /// Create a new reader id for this ring buffer, which starts reading at
+    /// the element with the sequence number `seq`, or the oldest element
+    /// stored if that one is gone.
+    pub fn new_reader_id_at(&mut self, seq: u64) -> ReaderId<T> {
+        self.maintain();
+        let seq = cmp::min(cmp::max(seq, self.oldest_retained()), self.written);
+        let (last_index, generation) = self.position_before(seq);
+        if seq < self.written {
+            // The next write has to keep the elements of the new reader.
+            self.available = 0;
+        }
+
+        self.alloc_reader_id(last_index, generation)
+    }
+
+    /// Create a new reader id for this ring buffer, which starts reading
+    /// `num` elements before the next write (if they're stored).
+    pub fn new_reader_id_with_history(&mut self, num: usize) -> ReaderId<T> {
+        let seq = self.written.saturating_sub(num as u64);
+
+        self.new_reader_id_at(seq)
+    }
+
+    /// Create a new reader id for this ring buffer, which starts at the
+    /// current position of `reader_id`, with the same lag and pause state.
+    pub fn fork_reader_id(&mut self, reader_id: &ReaderId<T>) -> ReaderId<T> {
+        self.maintain();
+        let reader = *self.reader_shared(reader_id);
+        let fork = self.alloc_reader_id(reader.last_index, reader.generation);
+        self.meta.reader_exclusive(fork.id).lagged = reader.lagged;
+        if let Some(paused_at) = reader.paused_at {
+            self.meta.pause(fork.id, paused_at);
+        }
+
+        fork
+    }
+
+    /// Pauses `reader_id`, so it doesn't keep elements from being overwritten
+    /// anymore.
+    ///
+    /// A paused reader doesn't observe any elements until it's resumed.
+    pub fn pause(&mut self, reader_id: &mut ReaderId<T>) {
+        let seq = self.written - self.pending(reader_id) as u64;
+        self.meta.pause(reader_id.id, seq);
+        let (last_index, generation) = (self.last_index.index, self.generation.0);
+        let reader = self.reader(reader_id);
+        reader.last_index = last_index;
+        reader.generation = generation;
+    }
+
+    /// Resumes a paused `reader_id` at the next written element.
+    ///
+    /// Returns the number of elements it skipped, or `0` if it wasn't paused.
+    pub fn resume(&mut self, reader_id: &mut ReaderId<T>) -> usize {
+        self.reader(reader_id);
+
+        match self.meta.resume(reader_id.id) {
+            Some(paused_at) => (self.written - paused_at) as usize,
+            None => 0,
+        }
+    }
+
+    /// Resumes a paused `reader_id` at the oldest element it missed which is
+    /// still stored.
+    ///
+    /// Returns the number of elements it skipped, or `0` if it wasn't paused.
+    pub fn resume_from_oldest(&mut self, reader_id: &mut ReaderId<T>) -> usize {
+        self.reader(reader_id);
+
+        match self.meta.resume(reader_id.id) {
+            Some(paused_at) => {
+                let seq = cmp::max(paused_at, self.oldest_retained());
+                // This also makes the next write keep the elements again.
+                self.seek(reader_id, seq)
+                    .expect("Sequence number is retained");
+
+                (seq - paused_at) as usize
+            }
+            None => 0,
+        }
+    }
+
+    /// Create a new reader id for this ring buffer, which only reads the
+    /// elements accepted by `filter`.
+    pub fn new_filtered_reader_id<F>(&mut self, filter: F) -> FilteredReaderId<T>
+    where
+        F: Fn(&T) -> bool + Send + Sync + UnwindSafe + RefUnwindSafe + 'static,
+    {
+        let reader_id = self.new_reader_id();
+        self.meta.set_filter(reader_id.id, Box::new(filter));
+
+        FilteredReaderId { reader_id }
+    }
+
+    fn alloc_reader_id(&mut self, last_index: usize, generation: usize) -> ReaderId<T> {
+        let id = self.meta.alloc(last_index, generation);
+        let reader = self.meta.reader_exclusive(id);
+        reader.last_read = self.generation.0;
+
+        ReaderId {
+            epoch: reader.epoch,
+            id,
+            marker: PhantomData,
+            reference: self.instance_id.reference(),
+            drop_notifier: NoSharedAccess::new(self.free_tx.get_mut().clone()),
+        }
+    }
+
+    /// Read data from the ring buffer, starting where the last read ended, and
+    /// up to where the last element was written.
+    ///
+    /// If the reader missed events because they got overwritten, the remaining
+    /// events are returned as usual.
+    pub fn read(&self, reader_id: &mut ReaderId<T>) -> StorageIterator<'_, T> {
+        match self.try_read(reader_id) {
+            Ok(iter) => iter,
+            // The lag has been reset, so this time the events are returned.
+            Err(ReadError::Lagged { .. }) => self.read(reader_id),
+            Err(e) => panic!("{}", e),
+        }
+    }
+
+    /// Read data from the ring buffer like `read`, but report errors instead
+    /// of panicking, including events the reader missed because they got
+    /// overwritten.
+    ///
+    /// In case the reader lagged behind, no events are returned; the next
+    /// read starts at the oldest event still in the buffer.
+    pub fn try_read(
+        &self,
+        reader_id: &mut ReaderId<T>,
+    ) -> Result<StorageIterator<'_, T>, ReadError> {
+        let reader = self.try_reader(reader_id)?;
+        if reader.lagged > 0 {
+            let missed = reader.lagged;
+            reader.lagged = 0;
+
+            return Err(ReadError::Lagged { missed });
+        }
+
+        Ok(self.read_reader(reader))
+    }
+
+    /// Moves the data `reader_id` hasn't read yet out of the ring buffer.
+    ///
+    /// This only works if no other reader has unread elements and no elements
+    /// are retained; otherwise `ReadError::Shared` is returned. All elements
+    /// the reader already read are dropped.
+    pub fn drain(
+        &mut self,
+        reader_id: &mut ReaderId<T>,
+    ) -> Result<DrainIterator<'_, T>, ReadError> {
+        self.maintain();
+        let (last, current_gen) = (self.last_index, self.generation.0);
+        self.try_reader(reader_id)?;
+        if self.retention > 0 || self.meta.others_pending(reader_id.id, last, current_gen) {
+            return Err(ReadError::Shared);
+        }
+
+        let reader = self.reader(reader_id);
+        let len = reader.pending(last, current_gen);
+        reader.last_index = last.index;
+        reader.generation = current_gen;
+        reader.lagged = 0;
+        // Paused readers must never point at the released elements.
+        self.meta.catch_up_paused(last.index, current_gen);
+
+        unsafe {
+            self.data.release(last, len);
+        }
+
+        let mut index = CircularIndex::new(last + 1, last.size);
+        index -= len;
+
+        Ok(DrainIterator {
+            data: &mut self.data,
+            index,
+            len,
+        })
+    }
+
+    /// Read data from the ring buffer like `read`, but return it as two
+    /// slices.
+    pub fn read_slices(&self, reader_id: &mut ReaderId<T>) -> (&[T], &[T]) {
+        self.read(reader_id).as_slices()
+    }
+
+    /// Read data from the ring buffer like `read`, but split it up into the
+    /// elements of each write.
+    pub fn read_batches(&self, reader_id: &mut ReaderId<T>) -> BatchedIterator<'_, T> {
+        BatchedIterator {
+            batches: &self.batches,
+            iter: self.read(reader_id),
+        }
+    }
+
+    /// Read data from the ring buffer like `read`, but yield every element
+    /// together with the time it was written at.
+    ///
+    /// Panics if no clock is set.
+    pub fn read_timestamped(&self, reader_id: &mut ReaderId<T>) -> TimestampedIterator<'_, T> {
+        assert!(self.clock.is_some(), "No clock set");
+        let iter = self.read(reader_id);
+        let seq = iter.seq;
+
+        TimestampedIterator {
+            batch: self
+                .batches
+                .partition_point(|batch| batch.start <= seq)
+                .saturating_sub(1),
+            batches: &self.batches,
+            iter,
+        }
+    }
+
+    /// Read the data accepted by the filter of `reader_id` from the ring
+    /// buffer, starting where the last read ended, and up to where the last
+    /// element was written.
+    pub fn read_filtered(&self, reader_id: &mut FilteredReaderId<T>) -> FilteredIterator<'_, T> {
+        let iter = self.read(&mut reader_id.reader_id);
+        let filter = self
+            .meta
+            .filter(reader_id.reader_id.id)
+            .expect("Filtered reader without filter");
+
+        FilteredIterator { iter, filter }
+    }
+
+    /// Read data from the ring buffer like `read_filtered`, but report errors
+    /// like `try_read`.
+    pub fn try_read_filtered(
+        &self,
+        reader_id: &mut FilteredReaderId<T>,
+    ) -> Result<FilteredIterator<'_, T>, ReadError> {
+        let iter = self.try_read(&mut reader_id.reader_id)?;
+        let filter = self
+            .meta
+            .filter(reader_id.reader_id.id)
+            .expect("Filtered reader without filter");
+
+        Ok(FilteredIterator { iter, filter })
+    }
+
+    /// Read data from the ring buffer like `read`, but only advance the reader
+    /// past the elements that actually got iterated once the returned iterator
+    /// is dropped.
+    pub fn read_tracked<'a>(&'a self, reader_id: &'a mut ReaderId<T>) -> TrackedIterator<'a, T> {
+        let reader = self.reader(reader_id);
+
+        TrackedIterator {
+            iter: self.iter_after(reader.last_index, reader.generation),
+            generation: self.generation.0,
+            reader,
+        }
+    }
+
+    /// Starts a read of the data written since the last read, which only
+    /// advances the reader once the returned transaction is committed.
+    pub fn read_transaction<'a>(&'a self, reader_id: &'a mut ReaderId<T>) -> Transaction<'a, T> {
+        let reader = self.reader(reader_id);
+
+        Transaction {
+            iter: self.iter_after(reader.last_index, reader.generation),
+            generation: self.generation.0,
+            reader,
+        }
+    }
+
+    /// Returns the number of events `reader_id` hasn't read yet, without
+    /// advancing it.
+    pub fn pending(&self, reader_id: &ReaderId<T>) -> usize {
+        self.reader_shared(reader_id)
+            .pending(self.last_index, self.generation.0)
+    }
+
+    /// Returns the events `reader_id` would get by `read`, without advancing
+    /// it.
+    pub fn peek(&self, reader_id: &ReaderId<T>) -> StorageIterator<'_, T> {
+        let reader = self.reader_shared(reader_id);
+
+        self.iter_after(reader.last_index, reader.generation)
+    }
+
+    // Borrowing `reader_id` mutably makes sure nobody else accesses the reader.
+    #[allow(clippy::mut_from_ref)]
+    fn reader(&self, reader_id: &mut ReaderId<T>) -> &mut Reader {
+        self.try_reader(reader_id)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    // Borrowing `reader_id` mutably makes sure nobody else accesses the reader.
+    #[allow(clippy::mut_from_ref)]
+    fn try_reader(&self, reader_id: &mut ReaderId<T>) -> Result<&mut Reader, ReadError> {
+        // Check if `reader_id` was actually created for this buffer.
+        // This is very important as `reader_id` is a token allowing memory access,
+        // and without this check a race could be caused by duplicate IDs.
+        if self.instance_id != reader_id.reference {
+            return Err(ReadError::ForeignChannel);
+        }
+
+        let epoch = reader_id.epoch;
+        let reader = self.meta.reader(reader_id).ok_or(ReadError::Unregistered)?;
+        if reader.epoch != epoch {
+            return Err(ReadError::Expired);
+        }
+        if !reader.active() {
+            return Err(ReadError::Unregistered);
+        }
+        reader.last_read = self.generation.0;
+
+        Ok(reader)
+    }
+
+    fn reader_shared(&self, reader_id: &ReaderId<T>) -> &Reader {
+        self.check_reader_id(reader_id);
+
+        let reader = self
+            .meta
+            .reader_shared(reader_id)
+            .unwrap_or_else(|| Self::not_registered(reader_id));
+        if reader.epoch != reader_id.epoch {
+            panic!("{}", ReadError::Expired);
+        }
+
+        reader
+    }
+
+    fn check_reader_id(&self, reader_id: &ReaderId<T>) {
+        // Check if `reader_id` was actually created for this buffer.
+        // This is very important as `reader_id` is a token allowing memory access,
+        // and without this check a race could be caused by duplicate IDs.
+        self.instance_id.assert_eq(&reader_id.reference);
+    }
+
+    fn not_registered(reader_id: &ReaderId<T>) -> ! {
+        panic!(
+            "ReaderId not registered: {}\n\
+             This usually means that this ReaderId \
+             was created by a different storage",
+            reader_id.id
+        )
+    }
+
+    fn read_reader(&self, reader: &mut Reader) -> StorageIterator<'_, T> {
+        let iter = self.iter_after(reader.last_index, reader.generation);
+        reader.last_index = self.last_index.index;
+        reader.generation = self.generation.0;
+
+        iter
+    }
+
+    /// Returns an iterator over the elements written after `last_read_index`
+    /// up to the last written element.
+    fn iter_after(&self, last_read_index: usize, gen: usize) -> StorageIterator<'_, T> {
         let mut index = CircularIndex::new(last_read_index, self.last_index.size);
-        index += 1;
-        if gen == self.generation.0 {
+        let len = match gen == self.generation.0 {
             // It is empty
-            index = CircularIndex::magic(index.size);
-        }
+            true => 0,
+            false => match self.last_index - last_read_index {
+                0 => index.size,
+                x => x,
+            },
+        };
+        index += 1;
 
-        let iter = StorageIterator {
+        StorageIterator {
             data: &self.data,
-            end: self.last_index.index,
             index,
-        };
+            len,
+            seq: self.written - len as u64,
+        }
+    }
+}
 
-        iter
+// Every write leaves the buffer in a consistent state, even if it panics,
+// and the clock, growth policy and filters have to be unwind safe.
+impl<T: UnwindSafe + RefUnwindSafe> UnwindSafe for RingBuffer<T> {}
+impl<T: RefUnwindSafe> RefUnwindSafe for RingBuffer<T> {}
+
+/// Records a write once it's done, which happens on unwinding, too.
+struct PendingWrite<'a, T: 'static> {
+    buffer: &'a mut RingBuffer<T>,
+    start: u64,
+    time: Duration,
+}
+
+impl<'a, T> Drop for PendingWrite<'a, T> {
+    fn drop(&mut self) {
+        if self.buffer.overwriting {
+            // The space left might still hold unread elements.
+            self.buffer.overwriting = false;
+            self.buffer.available = 0;
+        }
+        if self.buffer.written > self.start {
+            self.buffer.finish_write(self.start, self.time);
+        }
     }
 }
 
@@ -526,247 +1719,1768 @@ impl<T: Debug> Debug for RingBuffer<T> {
             .field("last_index", &self.last_index)
             .finish()
     }
-}
+}
+
+impl<T> Drop for RingBuffer<T> {
+    fn drop(&mut self) {
+        unsafe {
+            self.data.clean(self.last_index + 1);
+        }
+    }
+}
+
+/// Iterator over a slice of data in `RingBufferStorage`.
+#[derive(Debug)]
+pub struct StorageIterator<'a, T: 'a> {
+    data: &'a Data<T>,
+    /// Index of the next element
+    index: CircularIndex,
+    /// Number of remaining elements
+    len: usize,
+    /// Sequence number of the next element
+    seq: u64,
+}
+
+impl<'a, T> StorageIterator<'a, T> {
+    /// Returns the sequence number of the element returned by the next call
+    /// to `next`.
+    pub fn next_sequence(&self) -> u64 {
+        self.seq
+    }
+
+    /// Returns the remaining elements as two slices, which contain the
+    /// elements in order when chained.
+    ///
+    /// The second slice is only non-empty if the elements wrap around the end
+    /// of the ring buffer.
+    pub fn as_slices(&self) -> (&'a [T], &'a [T]) {
+        let data = self.data;
+        let (start, end, size) = (
+            self.index.index,
+            self.index.index + self.len,
+            self.index.size,
+        );
+
+        unsafe {
+            match self.len {
+                0 => (&[], &[]),
+                _ if end <= size => (data.slice(start..end), &[]),
+                _ => (data.slice(start..size), data.slice(0..end - size)),
+            }
+        }
+    }
+
+    /// Returns the `i`th remaining element, without advancing the iterator.
+    pub fn get(&self, i: usize) -> Option<&'a T> {
+        let data = self.data;
+
+        match i < self.len {
+            true => Some(unsafe { data.get(self.index + i) }),
+            false => None,
+        }
+    }
+
+    /// Splits off an iterator over the next `n` elements, advancing this one
+    /// past them.
+    fn split_front(&mut self, n: usize) -> StorageIterator<'a, T> {
+        let mut front = self.clone();
+        front.len = n;
+        self.index += n;
+        self.len -= n;
+        self.seq += n as u64;
+
+        front
+    }
+
+    /// Returns an iterator which yields the elements together with their
+    /// sequence numbers.
+    pub fn sequenced(self) -> SequencedIterator<'a, T> {
+        SequencedIterator { iter: self }
+    }
+}
+
+impl<'a, T> Iterator for StorageIterator<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let elem = self.get(0)?;
+        self.index += 1;
+        self.len -= 1;
+        self.seq += 1;
+
+        Some(elem)
+    }
+
+    fn nth(&mut self, n: usize) -> Option<&'a T> {
+        let skip = cmp::min(n, self.len);
+        self.index += skip;
+        self.len -= skip;
+        self.seq += skip as u64;
+
+        self.next()
+    }
+
+    fn last(mut self) -> Option<&'a T> {
+        self.next_back()
+    }
+
+    fn count(self) -> usize {
+        self.len
+    }
+
+    // Needed to fulfill contract of `ExactSizeIterator`
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for StorageIterator<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        let elem = self.get(self.len.checked_sub(1)?)?;
+        self.len -= 1;
+
+        Some(elem)
+    }
+
+    fn nth_back(&mut self, n: usize) -> Option<&'a T> {
+        self.len -= cmp::min(n, self.len);
+
+        self.next_back()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for StorageIterator<'a, T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<'a, T> Clone for StorageIterator<'a, T> {
+    fn clone(&self) -> Self {
+        StorageIterator {
+            data: self.data,
+            index: self.index,
+            len: self.len,
+            seq: self.seq,
+        }
+    }
+}
+
+/// Iterator over the elements of a slice of data in `RingBufferStorage`
+/// which are accepted by a filter.
+pub struct FilteredIterator<'a, T: 'a> {
+    iter: StorageIterator<'a, T>,
+    filter: &'a (dyn Fn(&T) -> bool + Send + Sync + UnwindSafe + RefUnwindSafe),
+}
+
+impl<'a, T> Iterator for FilteredIterator<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        let filter = self.filter;
+
+        self.iter.find(|elem| filter(elem))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (0, Some(self.iter.len()))
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for FilteredIterator<'a, T> {
+    fn next_back(&mut self) -> Option<&'a T> {
+        let filter = self.filter;
+
+        self.iter.rfind(|elem| filter(elem))
+    }
+}
+
+impl<'a, T: Debug> Debug for FilteredIterator<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("FilteredIterator")
+            .field("iter", &self.iter)
+            .finish()
+    }
+}
+
+/// Iterator over a slice of data in `RingBufferStorage`, which yields every
+/// element together with its sequence number.
+#[derive(Clone, Debug)]
+pub struct SequencedIterator<'a, T: 'a> {
+    iter: StorageIterator<'a, T>,
+}
+
+impl<'a, T> Iterator for SequencedIterator<'a, T> {
+    type Item = (u64, &'a T);
+
+    fn next(&mut self) -> Option<(u64, &'a T)> {
+        let seq = self.iter.seq;
+
+        self.iter.next().map(|elem| (seq, elem))
+    }
+
+    fn nth(&mut self, n: usize) -> Option<(u64, &'a T)> {
+        self.iter.nth(n).map(|elem| (self.iter.seq - 1, elem))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, T> DoubleEndedIterator for SequencedIterator<'a, T> {
+    fn next_back(&mut self) -> Option<(u64, &'a T)> {
+        // The element is right after the remaining ones.
+        self.iter
+            .next_back()
+            .map(|elem| (self.iter.seq + self.iter.len as u64, elem))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for SequencedIterator<'a, T> {
+    fn len(&self) -> usize {
+        self.iter.len()
+    }
+}
+
+/// Iterator over a slice of data in `RingBufferStorage`, which yields one
+/// `StorageIterator` per write the data was written with.
+#[derive(Clone, Debug)]
+pub struct BatchedIterator<'a, T: 'a> {
+    batches: &'a VecDeque<Batch>,
+    iter: StorageIterator<'a, T>,
+}
+
+impl<'a, T> Iterator for BatchedIterator<'a, T> {
+    type Item = StorageIterator<'a, T>;
+
+    fn next(&mut self) -> Option<StorageIterator<'a, T>> {
+        if self.iter.len == 0 {
+            return None;
+        }
+
+        let seq = self.iter.seq;
+        let next = self.batches.partition_point(|batch| batch.start <= seq);
+        let len = match self.batches.get(next) {
+            Some(end) => cmp::min((end.start - seq) as usize, self.iter.len),
+            None => self.iter.len,
+        };
+
+        Some(self.iter.split_front(len))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (cmp::min(self.iter.len, 1), Some(self.iter.len))
+    }
+}
+
+/// Iterator over a slice of data in `RingBufferStorage`, which yields every
+/// element together with the time it was written at.
+#[derive(Clone, Debug)]
+pub struct TimestampedIterator<'a, T: 'a> {
+    /// Index of the batch of the next element
+    batch: usize,
+    batches: &'a VecDeque<Batch>,
+    iter: StorageIterator<'a, T>,
+}
+
+impl<'a, T> Iterator for TimestampedIterator<'a, T> {
+    type Item = (Duration, &'a T);
+
+    fn next(&mut self) -> Option<(Duration, &'a T)> {
+        let seq = self.iter.seq;
+        let elem = self.iter.next()?;
+        while self
+            .batches
+            .get(self.batch + 1)
+            .is_some_and(|next| next.start <= seq)
+        {
+            self.batch += 1;
+        }
+
+        Some((self.batches[self.batch].time, elem))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for TimestampedIterator<'a, T> {
+    fn len(&self) -> usize {
+        self.iter.len()
+    }
+}
+
+/// Iterator over a slice of data in `RingBufferStorage`, which advances
+/// its reader only past the elements that were yielded.
+///
+/// The reader is updated when this iterator gets dropped.
+#[derive(Debug)]
+pub struct TrackedIterator<'a, T: 'a> {
+    iter: StorageIterator<'a, T>,
+    /// Generation at the time of the read
+    generation: usize,
+    reader: &'a mut Reader,
+}
+
+impl<'a, T> Iterator for TrackedIterator<'a, T> {
+    type Item = &'a T;
+
+    fn next(&mut self) -> Option<&'a T> {
+        self.iter.next()
+    }
+
+    // Skipped elements count as read.
+    fn nth(&mut self, n: usize) -> Option<&'a T> {
+        self.iter.nth(n)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.iter.size_hint()
+    }
+}
+
+impl<'a, T> ExactSizeIterator for TrackedIterator<'a, T> {
+    fn len(&self) -> usize {
+        self.iter.len()
+    }
+}
+
+impl<'a, T> Drop for TrackedIterator<'a, T> {
+    fn drop(&mut self) {
+        self.reader.last_index = self.iter.index - 1;
+        // Otherwise, the reader keeps its old generation, since there are
+        // still unread elements.
+        if self.iter.len == 0 {
+            self.reader.generation = self.generation;
+        }
+    }
+}
+
+/// A pending read of a slice of data in `RingBufferStorage`.
+///
+/// The reader is only advanced by `commit`; dropping the transaction leaves
+/// it untouched.
+#[derive(Debug)]
+pub struct Transaction<'a, T: 'a> {
+    iter: StorageIterator<'a, T>,
+    /// Generation at the time of the read
+    generation: usize,
+    reader: &'a mut Reader,
+}
+
+impl<'a, T> Transaction<'a, T> {
+    /// Returns an iterator over the elements of this transaction.
+    ///
+    /// This can be called any number of times.
+    pub fn iter(&self) -> StorageIterator<'a, T> {
+        self.iter.clone()
+    }
+
+    /// Returns the number of elements in this transaction.
+    pub fn len(&self) -> usize {
+        self.iter.len()
+    }
+
+    /// Returns `true` if there are no elements in this transaction.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Marks all elements of this transaction as read.
+    pub fn commit(self) {
+        let mut end = self.iter.index;
+        end += self.iter.len;
+        self.reader.last_index = end - 1;
+        self.reader.generation = self.generation;
+    }
+
+    /// Leaves the reader where it was, so the elements are returned again by
+    /// the next read.
+    ///
+    /// This is the same as dropping the transaction.
+    pub fn rollback(self) {}
+}
+
+impl<'a, T> IntoIterator for &Transaction<'a, T> {
+    type IntoIter = StorageIterator<'a, T>;
+    type Item = &'a T;
+
+    fn into_iter(self) -> StorageIterator<'a, T> {
+        self.iter()
+    }
+}
+
+/// Iterator moving the elements of a slice of data out of
+/// `RingBufferStorage`.
+///
+/// Elements which weren't yielded are dropped together with the iterator.
+pub struct DrainIterator<'a, T: 'a> {
+    data: &'a mut Data<T>,
+    index: CircularIndex,
+    len: usize,
+}
+
+impl<'a, T> Iterator for DrainIterator<'a, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.len == 0 {
+            return None;
+        }
+
+        let elem = unsafe { self.data.take(self.index.index) };
+        self.index += 1;
+        self.len -= 1;
+
+        Some(elem)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.len, Some(self.len))
+    }
+}
+
+impl<'a, T> ExactSizeIterator for DrainIterator<'a, T> {
+    fn len(&self) -> usize {
+        self.len
+    }
+}
+
+impl<'a, T> Drop for DrainIterator<'a, T> {
+    fn drop(&mut self) {
+        self.for_each(drop);
+    }
+}
+
+impl<'a, T> Debug for DrainIterator<'a, T> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DrainIterator")
+            .field("index", &self.index)
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Clone, PartialEq)]
+    struct Test {
+        pub id: u32,
+    }
+
+    #[allow(dead_code)]
+    #[derive(Debug, Clone, PartialEq)]
+    struct Test2 {
+        pub id: u32,
+    }
+
+    #[test]
+    fn test_size() {
+        let mut buffer = RingBuffer::<i32>::new(4);
+
+        buffer.single_write(55);
+
+        let mut reader = buffer.new_reader_id();
+
+        buffer.iter_write(0..16);
+        assert_eq!(buffer.read(&mut reader).len(), 16);
+
+        buffer.iter_write(0..6);
+        assert_eq!(buffer.read(&mut reader).len(), 6);
+    }
+
+    #[test]
+    fn test_circular() {
+        let mut buffer = RingBuffer::<i32>::new(4);
+
+        buffer.single_write(55);
+
+        let mut reader = buffer.new_reader_id();
+
+        buffer.iter_write(0..4);
+        assert_eq!(
+            buffer.read(&mut reader).cloned().collect::<Vec<_>>(),
+            vec![0, 1, 2, 3]
+        );
+    }
+
+    #[test]
+    fn test_empty_write() {
+        let mut buffer = RingBuffer::<Test>::new(10);
+        buffer.drain_vec_write(&mut vec![]);
+        assert_eq!(buffer.data.num_initialized(), 0);
+    }
+
+    #[test]
+    fn test_too_large_write() {
+        let mut buffer = RingBuffer::<Test>::new(10);
+        // Events just go off into the void if there's no reader registered.
+        let _reader = buffer.new_reader_id();
+        buffer.drain_vec_write(&mut events(15));
+        assert_eq!(buffer.data.num_initialized(), 15);
+    }
+
+    #[test]
+    fn test_empty_read() {
+        let mut buffer = RingBuffer::<Test>::new(10);
+        let mut reader_id = buffer.new_reader_id();
+        let data = buffer.read(&mut reader_id);
+        assert_eq!(Vec::<Test>::default(), data.cloned().collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn test_empty_read_write_before_id() {
+        let mut buffer = RingBuffer::<Test>::new(10);
+        buffer.drain_vec_write(&mut events(2));
+        let mut reader_id = buffer.new_reader_id();
+        let data = buffer.read(&mut reader_id);
+        assert_eq!(Vec::<Test>::default(), data.cloned().collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn test_read() {
+        let mut buffer = RingBuffer::<Test>::new(10);
+        let mut reader_id = buffer.new_reader_id();
+        buffer.drain_vec_write(&mut events(2));
+        assert_eq!(
+            vec![Test { id: 0 }, Test { id: 1 }],
+            buffer.read(&mut reader_id).cloned().collect::<Vec<_>>()
+        );
+
+        assert_eq!(
+            Vec::<Test>::new(),
+            buffer.read(&mut reader_id).cloned().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_write_overflow() {
+        let mut buffer = RingBuffer::<Test>::new(3);
+        let mut reader_id = buffer.new_reader_id();
+        buffer.drain_vec_write(&mut events(4));
+        let data = buffer.read(&mut reader_id);
+        assert_eq!(
+            vec![
+                Test { id: 0 },
+                Test { id: 1 },
+                Test { id: 2 },
+                Test { id: 3 },
+            ],
+            data.cloned().collect::<Vec<_>>()
+        );
+    }
+
+    /// If you're getting a compilation error here this test has failed!
+    #[test]
+    fn test_send_sync() {
+        trait SendSync: Send + Sync {
+            fn is_send_sync() -> bool;
+        }
+
+        impl<T> SendSync for T
+        where
+            T: Send + Sync,
+        {
+            fn is_send_sync() -> bool {
+                true
+            }
+        }
+
+        assert!(RingBuffer::<Test>::is_send_sync());
+        assert!(ReaderId::<Test>::is_send_sync());
+    }
+
+    #[test]
+    fn test_reader_reuse() {
+        let mut buffer = RingBuffer::<Test>::new(3);
+        {
+            let _reader_id = buffer.new_reader_id();
+        }
+        let _reader_id = buffer.new_reader_id();
+        assert_eq!(_reader_id.id, 0);
+        assert_eq!(buffer.meta.readers.len(), 1);
+    }
+
+    #[test]
+    fn test_prevent_excess_growth() {
+        let mut buffer = RingBuffer::<Test>::new(3);
+        let mut reader_id = buffer.new_reader_id();
+        println!("Initial buffer state: {:#?}", buffer);
+        println!("--- first write ---");
+        buffer.drain_vec_write(&mut events(2));
+        println!("--- second write ---");
+        buffer.drain_vec_write(&mut events(2));
+        println!("--- writes complete ---");
+        // we wrote 0,1,0,1, if the buffer grew correctly we'll get all of these back.
+        assert_eq!(
+            vec![
+                Test { id: 0 },
+                Test { id: 1 },
+                Test { id: 0 },
+                Test { id: 1 },
+            ],
+            buffer.read(&mut reader_id).cloned().collect::<Vec<_>>()
+        );
+
+        buffer.drain_vec_write(&mut events(4));
+        // After writing 4 more events the buffer should have no reason to grow beyond 6
+        // (2 * 3).
+        assert_eq!(buffer.data.num_initialized(), 6);
+        assert_eq!(
+            vec![
+                Test { id: 0 },
+                Test { id: 1 },
+                Test { id: 2 },
+                Test { id: 3 },
+            ],
+            buffer.read(&mut reader_id).cloned().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_write_slice() {
+        let mut buffer = RingBuffer::<Test>::new(10);
+        let mut reader_id = buffer.new_reader_id();
+        buffer.iter_write(events(2));
+        let data = buffer.read(&mut reader_id);
+        assert_eq!(
+            vec![Test { id: 0 }, Test { id: 1 }],
+            data.cloned().collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn iter_write_empty() {
+        let mut buffer = RingBuffer::<Test>::new(10);
+        let mut reader_id = buffer.new_reader_id();
+        buffer.iter_write(Vec::new());
+        let mut data = buffer.read(&mut reader_id);
+        assert_eq!(None, data.next());
+    }
+
+    #[test]
+    fn test_too_large_write_without_reader() {
+        let mut buffer = RingBuffer::<Test>::new(4);
+        buffer.drain_vec_write(&mut events(10));
+        let mut reader_id = buffer.new_reader_id();
+        buffer.drain_vec_write(&mut events(3));
+        assert_eq!(buffer.read(&mut reader_id).len(), 3);
+    }
+
+    #[test]
+    fn test_bounded_growth() {
+        let mut buffer = RingBuffer::<Test>::new_bounded(4, 6);
+        let mut reader_id = buffer.new_reader_id();
+        buffer.drain_vec_write(&mut events(5));
+        assert_eq!(buffer.data.num_initialized(), 5);
+        assert_eq!(buffer.last_index.size, 6);
+        assert_eq!(buffer.read(&mut reader_id).len(), 5);
+    }
+
+    #[test]
+    fn test_bounded_overwrite() {
+        let mut buffer = RingBuffer::<Test>::new_bounded(2, 4);
+        let mut reader_id = buffer.new_reader_id();
+        buffer.drain_vec_write(&mut events(3));
+        buffer.drain_vec_write(&mut events(3));
+        assert_eq!(buffer.last_index.size, 4);
+
+        assert_eq!(
+            buffer.try_read(&mut reader_id).unwrap_err(),
+            ReadError::Lagged { missed: 2 }
+        );
+        assert_eq!(
+            vec![
+                Test { id: 2 },
+                Test { id: 0 },
+                Test { id: 1 },
+                Test { id: 2 },
+            ],
+            buffer
+                .try_read(&mut reader_id)
+                .unwrap()
+                .cloned()
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(buffer.try_read(&mut reader_id).unwrap().len(), 0);
+    }
+
+    #[test]
+    fn test_bounded_write_larger_than_buffer() {
+        let mut buffer = RingBuffer::<Test>::new_bounded(2, 4);
+        let mut reader_id = buffer.new_reader_id();
+        buffer.drain_vec_write(&mut events(7));
+
+        // `read` skips the missed events silently
+        assert_eq!(
+            vec![
+                Test { id: 3 },
+                Test { id: 4 },
+                Test { id: 5 },
+                Test { id: 6 },
+            ],
+            buffer.read(&mut reader_id).cloned().collect::<Vec<_>>()
+        );
+        assert!(buffer.try_read(&mut reader_id).is_ok());
+    }
+
+    #[test]
+    fn test_try_write_full() {
+        let mut buffer = RingBuffer::<Test>::new_bounded(2, 4);
+        let mut reader_id = buffer.new_reader_id();
+        assert!(buffer.try_iter_write(events(3)).is_ok());
+        assert_eq!(buffer.last_index.size, 4);
+
+        let rejected = buffer.try_iter_write(events(2)).unwrap_err();
+        assert_eq!(rejected.into_inner().len(), 2);
+        assert!(buffer.try_single_write(Test { id: 3 }).is_ok());
+        assert_eq!(
+            buffer.try_single_write(Test { id: 4 }),
+            Err(Full(Test { id: 4 }))
+        );
+
+        assert_eq!(buffer.try_read(&mut reader_id).unwrap().len(), 4);
+        assert!(buffer.try_iter_write(events(4)).is_ok());
+        assert_eq!(buffer.try_read(&mut reader_id).unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_try_write_unbounded() {
+        let mut buffer = RingBuffer::<Test>::new(2);
+        let mut reader_id = buffer.new_reader_id();
+        assert!(buffer.try_iter_write(events(3)).is_ok());
+        assert!(buffer.try_iter_write(events(3)).is_ok());
+        assert_eq!(buffer.read(&mut reader_id).len(), 6);
+    }
+
+    #[test]
+    fn test_pending_peek() {
+        let mut buffer = RingBuffer::<Test>::new(3);
+        let mut reader_id = buffer.new_reader_id();
+        assert_eq!(buffer.pending(&reader_id), 0);
+        assert_eq!(buffer.peek(&reader_id).len(), 0);
+
+        buffer.drain_vec_write(&mut events(2));
+        buffer.drain_vec_write(&mut events(2));
+        assert_eq!(buffer.pending(&reader_id), 4);
+        assert_eq!(
+            buffer.peek(&reader_id).cloned().collect::<Vec<_>>(),
+            buffer.read(&mut reader_id).cloned().collect::<Vec<_>>()
+        );
+        assert_eq!(buffer.pending(&reader_id), 0);
+        assert_eq!(buffer.peek(&reader_id).len(), 0);
+    }
+
+    #[test]
+    fn test_read_tracked() {
+        let mut buffer = RingBuffer::<Test>::new(4);
+        let mut reader_id = buffer.new_reader_id();
+        buffer.drain_vec_write(&mut events(3));
+
+        {
+            let mut tracked = buffer.read_tracked(&mut reader_id);
+            assert_eq!(tracked.next(), Some(&Test { id: 0 }));
+        }
+        assert_eq!(buffer.pending(&reader_id), 2);
+
+        // Nothing iterated, nothing consumed
+        buffer.read_tracked(&mut reader_id);
+        assert_eq!(buffer.pending(&reader_id), 2);
+
+        buffer.drain_vec_write(&mut events(4));
+        assert_eq!(
+            buffer
+                .read_tracked(&mut reader_id)
+                .take(4)
+                .cloned()
+                .collect::<Vec<_>>(),
+            vec![
+                Test { id: 1 },
+                Test { id: 2 },
+                Test { id: 0 },
+                Test { id: 1 },
+            ]
+        );
+        assert_eq!(
+            buffer
+                .read_tracked(&mut reader_id)
+                .cloned()
+                .collect::<Vec<_>>(),
+            vec![Test { id: 2 }, Test { id: 3 }]
+        );
+        assert_eq!(buffer.pending(&reader_id), 0);
+        assert_eq!(buffer.read(&mut reader_id).len(), 0);
+    }
+
+    #[test]
+    fn test_read_tracked_lagged() {
+        let mut buffer = RingBuffer::<i32>::new_bounded(2, 2);
+        let mut reader_id = buffer.new_reader_id();
+        buffer.iter_write(0..3);
+
+        // The lag is still reported by the next read
+        assert_eq!(buffer.read_tracked(&mut reader_id).count(), 2);
+        assert_eq!(
+            buffer.try_read(&mut reader_id).err(),
+            Some(ReadError::Lagged { missed: 1 })
+        );
+    }
+
+    #[test]
+    fn test_read_transaction_lagged() {
+        let mut buffer = RingBuffer::<i32>::new_bounded(2, 2);
+        let mut reader_id = buffer.new_reader_id();
+        buffer.iter_write(0..3);
+
+        // The lag is still reported by the next read
+        let transaction = buffer.read_transaction(&mut reader_id);
+        assert_eq!(transaction.len(), 2);
+        transaction.commit();
+        assert_eq!(
+            buffer.try_read(&mut reader_id).err(),
+            Some(ReadError::Lagged { missed: 1 })
+        );
+    }
+
+    #[test]
+    fn test_read_transaction() {
+        let mut buffer = RingBuffer::<Test>::new(2);
+        let mut reader_id = buffer.new_reader_id();
+        buffer.drain_vec_write(&mut events(2));
+
+        let transaction = buffer.read_transaction(&mut reader_id);
+        assert_eq!(transaction.len(), 2);
+        assert_eq!(transaction.iter().count(), 2);
+        transaction.rollback();
+
+        {
+            let _transaction = buffer.read_transaction(&mut reader_id);
+        }
+
+        // The uncommitted events still count as unread
+        buffer.drain_vec_write(&mut events(2));
+        assert_eq!(buffer.last_index.size, 4);
+
+        let transaction = buffer.read_transaction(&mut reader_id);
+        assert_eq!(
+            transaction.iter().cloned().collect::<Vec<_>>(),
+            vec![
+                Test { id: 0 },
+                Test { id: 1 },
+                Test { id: 0 },
+                Test { id: 1 },
+            ]
+        );
+        transaction.commit();
+        assert_eq!(buffer.pending(&reader_id), 0);
+        assert!(buffer.read_transaction(&mut reader_id).is_empty());
+    }
+
+    #[test]
+    fn test_sequence_numbers() {
+        let mut buffer = RingBuffer::<Test>::new(2);
+        let mut reader_id = buffer.new_reader_id();
+        assert_eq!(buffer.single_write(Test { id: 0 }), 0);
+        assert_eq!(buffer.drain_vec_write(&mut events(3)), 1..4);
+        assert_eq!(buffer.iter_write(Vec::new()), 4..4);
+
+        let read = buffer.read(&mut reader_id);
+        assert_eq!(read.next_sequence(), 0);
+        assert_eq!(
+            read.sequenced().map(|(seq, _)| seq).collect::<Vec<_>>(),
+            vec![0, 1, 2, 3]
+        );
+
+        assert_eq!(buffer.drain_vec_write(&mut events(2)), 4..6);
+        assert_eq!(buffer.read(&mut reader_id).next_sequence(), 4);
+    }
+
+    #[test]
+    fn test_seek() {
+        let mut buffer = RingBuffer::<Test>::new(4);
+        let mut reader_id = buffer.new_reader_id();
+        buffer.drain_vec_write(&mut events(4));
+        assert_eq!(buffer.read(&mut reader_id).len(), 4);
+        buffer.drain_vec_write(&mut events(3));
+        assert_eq!(buffer.oldest_retained(), 3);
+
+        buffer.seek(&mut reader_id, 3).unwrap();
+        assert_eq!(buffer.pending(&reader_id), 4);
+        assert_eq!(
+            buffer
+                .read(&mut reader_id)
+                .sequenced()
+                .map(|(seq, elem)| (seq, elem.id))
+                .collect::<Vec<_>>(),
+            vec![(3, 3), (4, 0), (5, 1), (6, 2)]
+        );
+
+        buffer.seek(&mut reader_id, 5).unwrap();
+        assert_eq!(buffer.pending(&reader_id), 2);
+        buffer.seek(&mut reader_id, 7).unwrap();
+        assert_eq!(buffer.read(&mut reader_id).len(), 0);
+
+        assert_eq!(
+            buffer.seek(&mut reader_id, 2),
+            Err(SeekError::Overwritten { oldest: 3 })
+        );
+        assert_eq!(
+            buffer.seek(&mut reader_id, 8),
+            Err(SeekError::NotWritten { next: 7 })
+        );
+
+        // Seeking back keeps the events from getting overwritten
+        buffer.seek(&mut reader_id, 4).unwrap();
+        buffer.drain_vec_write(&mut events(2));
+        assert_eq!(buffer.read(&mut reader_id).next_sequence(), 4);
+    }
+
+    #[test]
+    fn test_seek_back_then_write() {
+        let mut buffer = RingBuffer::<i32>::new(8);
+        let mut reader_id = buffer.new_reader_id();
+        buffer.iter_write(0..8);
+        buffer.read(&mut reader_id);
+        buffer.single_write(8);
+        buffer.read(&mut reader_id);
+
+        buffer.seek(&mut reader_id, 1).unwrap();
+        assert_eq!(buffer.pending(&reader_id), 8);
+        buffer.iter_write(9..16);
+        assert_eq!(
+            buffer.read(&mut reader_id).cloned().collect::<Vec<_>>(),
+            (1..16).collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_retention() {
+        let mut buffer = RingBuffer::<Test>::new(4);
+        buffer.set_retention(3);
+        buffer.drain_vec_write(&mut events(2));
+        buffer.drain_vec_write(&mut events(3));
+        assert_eq!(buffer.last_index.size, 8);
+        assert_eq!(buffer.oldest_retained(), 0);
+
+        let mut reader_id = buffer.new_reader_id_with_history(4);
+        assert_eq!(
+            vec![
+                Test { id: 1 },
+                Test { id: 0 },
+                Test { id: 1 },
+                Test { id: 2 },
+            ],
+            buffer.read(&mut reader_id).cloned().collect::<Vec<_>>()
+        );
+
+        buffer.drain_vec_write(&mut events(6));
+        assert_eq!(buffer.last_index.size, 16);
+        assert_eq!(buffer.read(&mut reader_id).len(), 6);
+
+        // Without retention the elements get overwritten again
+        buffer.set_retention(0);
+        buffer.drain_vec_write(&mut events(16));
+        assert_eq!(buffer.last_index.size, 16);
+    }
+
+    #[test]
+    fn test_reader_at_oldest() {
+        let mut buffer = RingBuffer::<Test>::new(4);
+        buffer.drain_vec_write(&mut events(6));
+        let mut reader_id = buffer.new_reader_id_at(0);
+        assert_eq!(buffer.pending(&reader_id), 4);
+        assert_eq!(buffer.read(&mut reader_id).next_sequence(), 2);
+
+        let mut reader_id = buffer.new_reader_id_with_history(2);
+        assert_eq!(buffer.read(&mut reader_id).next_sequence(), 4);
+        let reader_id = buffer.new_reader_id_with_history(0);
+        assert_eq!(buffer.pending(&reader_id), 0);
+    }
+
+    #[test]
+    fn test_reader_with_history_beyond_retention() {
+        let mut buffer = RingBuffer::<i32>::new(8);
+        buffer.set_retention(1);
+        let mut reader_id = buffer.new_reader_id();
+        buffer.iter_write(0..8);
+        buffer.read(&mut reader_id);
+        buffer.single_write(8);
+        buffer.read(&mut reader_id);
+
+        let mut history = buffer.new_reader_id_with_history(4);
+        buffer.iter_write(9..15);
+        assert_eq!(
+            buffer.read(&mut history).cloned().collect::<Vec<_>>(),
+            (5..15).collect::<Vec<_>>()
+        );
+        assert_eq!(buffer.read(&mut reader_id).len(), 6);
+    }
+
+    #[test]
+    fn test_fork_reader() {
+        let mut buffer = RingBuffer::<Test>::new(4);
+        let mut reader_id = buffer.new_reader_id();
+        buffer.drain_vec_write(&mut events(3));
+
+        let mut fork = buffer.fork_reader_id(&reader_id);
+        assert_ne!(fork.id, reader_id.id);
+        assert_eq!(buffer.pending(&fork), 3);
+        assert_eq!(
+            buffer.read(&mut fork).cloned().collect::<Vec<_>>(),
+            buffer.read(&mut reader_id).cloned().collect::<Vec<_>>()
+        );
+
+        let fork = buffer.fork_reader_id(&reader_id);
+        assert_eq!(buffer.pending(&fork), 0);
+    }
+
+    #[test]
+    fn test_fork_lagged_reader() {
+        let mut buffer = RingBuffer::<i32>::new_bounded(2, 2);
+        let mut reader_id = buffer.new_reader_id();
+        buffer.iter_write(0..3);
+
+        let mut fork = buffer.fork_reader_id(&reader_id);
+        assert_eq!(
+            buffer.try_read(&mut fork).err(),
+            Some(ReadError::Lagged { missed: 1 })
+        );
+        assert_eq!(
+            buffer.try_read(&mut reader_id).err(),
+            Some(ReadError::Lagged { missed: 1 })
+        );
+    }
+
+    #[test]
+    fn test_fork_paused_reader() {
+        let mut buffer = RingBuffer::<i32>::new(4);
+        let mut reader_id = buffer.new_reader_id();
+        buffer.iter_write(0..2);
+        buffer.pause(&mut reader_id);
+        buffer.iter_write(2..4);
+
+        // The fork is paused as well, and resumes where `reader_id` would
+        let mut fork = buffer.fork_reader_id(&reader_id);
+        assert!(!buffer.would_write());
+        buffer.iter_write(4..8);
+        assert_eq!(buffer.capacity(), 4);
+        assert_eq!(buffer.resume_from_oldest(&mut fork), 4);
+        assert_eq!(buffer.resume_from_oldest(&mut reader_id), 4);
+        assert_eq!(
+            buffer.read(&mut fork).cloned().collect::<Vec<_>>(),
+            vec![4, 5, 6, 7]
+        );
+    }
+
+    #[test]
+    fn test_cursor() {
+        let mut buffer = RingBuffer::<Test>::new(4);
+        let mut reader_id = buffer.new_reader_id();
+        buffer.drain_vec_write(&mut events(2));
+        let cursor = buffer.cursor(&reader_id);
+        assert_eq!(cursor.sequence(), 0);
+
+        assert_eq!(buffer.read(&mut reader_id).len(), 2);
+        assert_eq!(buffer.cursor(&reader_id).sequence(), 2);
+        buffer.restore(&mut reader_id, cursor).unwrap();
+        assert_eq!(buffer.read(&mut reader_id).len(), 2);
+
+        let other = RingBuffer::<Test>::new(4);
+        let other_cursor = ReaderCursor {
+            channel: other.instance_id.serial(),
+            sequence: 0,
+        };
+        assert_eq!(
+            buffer.restore(&mut reader_id, other_cursor),
+            Err(SeekError::ForeignCursor)
+        );
+
+        buffer.drain_vec_write(&mut events(4));
+        assert_eq!(buffer.read(&mut reader_id).len(), 4);
+        assert_eq!(
+            buffer.restore(&mut reader_id, cursor),
+            Err(SeekError::Overwritten { oldest: 2 })
+        );
+    }
+
+    #[test]
+    fn test_cursor_paused() {
+        let mut buffer = RingBuffer::<i32>::new(8);
+        let mut reader_id = buffer.new_reader_id();
+        buffer.iter_write(0..4);
+        buffer.pause(&mut reader_id);
+        buffer.iter_write(4..6);
+
+        // The cursor keeps the events the paused reader missed
+        let cursor = buffer.cursor(&reader_id);
+        assert_eq!(cursor.sequence(), 0);
+        buffer.resume(&mut reader_id);
+        buffer.restore(&mut reader_id, cursor).unwrap();
+        assert_eq!(
+            buffer.read(&mut reader_id).cloned().collect::<Vec<_>>(),
+            (0..6).collect::<Vec<_>>()
+        );
+    }
 
-impl<T> Drop for RingBuffer<T> {
-    fn drop(&mut self) {
-        unsafe {
-            self.data.clean(self.last_index + 1);
-        }
+    #[test]
+    fn test_filtered_reader() {
+        let mut buffer = RingBuffer::<Test>::new(4);
+        let mut filtered = buffer.new_filtered_reader_id(|t: &Test| t.id >= 2);
+        buffer.drain_vec_write(&mut events(5));
+        assert_eq!(
+            vec![Test { id: 2 }, Test { id: 3 }, Test { id: 4 }],
+            buffer
+                .read_filtered(&mut filtered)
+                .cloned()
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(buffer.read_filtered(&mut filtered).count(), 0);
+
+        assert!(buffer.would_write_elem(&Test { id: 2 }));
+        assert!(!buffer.would_write_elem(&Test { id: 1 }));
+
+        let _all = buffer.new_reader_id();
+        assert!(buffer.would_write_elem(&Test { id: 1 }));
+
+        // The filter gets removed together with the reader
+        drop(filtered);
+        let reader_id = buffer.new_reader_id();
+        assert!(buffer.meta.filter(reader_id.id).is_none());
     }
-}
 
-/// Iterator over a slice of data in `RingBufferStorage`.
-#[derive(Debug)]
-pub struct StorageIterator<'a, T: 'a> {
-    data: &'a Data<T>,
-    /// Inclusive end
-    end: usize,
-    index: CircularIndex,
-}
+    #[test]
+    fn test_try_read_filtered() {
+        let mut buffer = RingBuffer::<i32>::new_bounded(4, 4);
+        let mut filtered = buffer.new_filtered_reader_id(|&i: &i32| i % 2 == 0);
+        buffer.iter_write(0..6);
+        assert_eq!(buffer.pending(filtered.reader_id()), 4);
+        assert_eq!(
+            buffer.try_read_filtered(&mut filtered).err(),
+            Some(ReadError::Lagged { missed: 2 })
+        );
+        assert_eq!(
+            buffer
+                .try_read_filtered(&mut filtered)
+                .unwrap()
+                .cloned()
+                .collect::<Vec<_>>(),
+            vec![2, 4]
+        );
 
-impl<'a, T> Iterator for StorageIterator<'a, T> {
-    type Item = &'a T;
+        // Paused filtered readers don't keep events either
+        buffer.pause(filtered.reader_id_mut());
+        buffer.iter_write(6..10);
+        assert_eq!(buffer.resume_from_oldest(filtered.reader_id_mut()), 0);
+        assert_eq!(
+            buffer
+                .read_filtered(&mut filtered)
+                .cloned()
+                .collect::<Vec<_>>(),
+            vec![6, 8]
+        );
+    }
 
-    fn next(&mut self) -> Option<&'a T> {
-        self.index
-            .step(self.end)
-            .map(|i| unsafe { self.data.get(i) })
+    #[test]
+    fn test_pause_resume() {
+        let mut buffer = RingBuffer::<Test>::new(4);
+        let mut reader_id = buffer.new_reader_id();
+        buffer.drain_vec_write(&mut events(1));
+        buffer.pause(&mut reader_id);
+        assert!(!buffer.would_write());
+        assert_eq!(buffer.pending(&reader_id), 0);
+
+        buffer.drain_vec_write(&mut events(3));
+        buffer.drain_vec_write(&mut events(3));
+        assert_eq!(buffer.last_index.size, 4);
+        assert_eq!(buffer.read(&mut reader_id).len(), 0);
+
+        // Paused readers don't make the buffer grow, even for large writes
+        buffer.drain_vec_write(&mut events(5));
+        assert_eq!(buffer.last_index.size, 4);
+
+        assert_eq!(buffer.resume(&mut reader_id), 12);
+        assert!(buffer.would_write());
+        assert_eq!(buffer.resume(&mut reader_id), 0);
+        buffer.drain_vec_write(&mut events(2));
+        assert_eq!(buffer.read(&mut reader_id).len(), 2);
     }
 
-    // Needed to fulfill contract of `ExactSizeIterator`
-    fn size_hint(&self) -> (usize, Option<usize>) {
-        let len = self.len();
+    #[test]
+    fn test_resume_from_oldest() {
+        let mut buffer = RingBuffer::<Test>::new(4);
+        let mut reader_id = buffer.new_reader_id();
+        buffer.drain_vec_write(&mut events(2));
+        buffer.pause(&mut reader_id);
+        buffer.drain_vec_write(&mut events(3));
+
+        assert_eq!(buffer.resume_from_oldest(&mut reader_id), 1);
+        assert_eq!(
+            buffer
+                .read(&mut reader_id)
+                .sequenced()
+                .map(|(seq, _)| seq)
+                .collect::<Vec<_>>(),
+            vec![1, 2, 3, 4]
+        );
 
-        (len, Some(len))
+        // Dropping a paused reader frees it
+        buffer.pause(&mut reader_id);
+        drop(reader_id);
+        assert!(!buffer.would_write());
+        let _reader_id = buffer.new_reader_id();
+        assert!(buffer.meta.paused.is_empty());
+        assert_eq!(buffer.meta.active, 1);
     }
-}
 
-impl<'a, T> ExactSizeIterator for StorageIterator<'a, T> {
-    fn len(&self) -> usize {
-        match self.index.is_magic() {
-            true => 0,
-            false => (CircularIndex::new(self.end, self.index.size) - self.index.index) + 1,
-        }
+    #[test]
+    fn test_resume_from_oldest_then_write() {
+        let mut buffer = RingBuffer::<i32>::new(8);
+        let mut reader_id = buffer.new_reader_id();
+        let mut paused = buffer.new_reader_id();
+        buffer.pause(&mut paused);
+        buffer.iter_write(0..8);
+        buffer.read(&mut reader_id);
+        buffer.single_write(8);
+        buffer.read(&mut reader_id);
+
+        assert_eq!(buffer.resume_from_oldest(&mut paused), 1);
+        assert_eq!(buffer.pending(&paused), 8);
+        buffer.iter_write(9..16);
+        assert_eq!(
+            buffer.read(&mut paused).cloned().collect::<Vec<_>>(),
+            (1..16).collect::<Vec<_>>()
+        );
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+    #[test]
+    fn test_reader_expiry() {
+        let mut buffer = RingBuffer::<Test>::new(4);
+        buffer.set_reader_expiry(Some(1));
+        let mut idle = buffer.new_reader_id();
+        let mut active = buffer.new_reader_id();
+        let mut paused = buffer.new_reader_id();
+        buffer.pause(&mut paused);
+
+        for _ in 0..3 {
+            buffer.drain_vec_write(&mut events(3));
+            assert_eq!(buffer.read(&mut active).len(), 3);
+        }
 
-    #[derive(Debug, Clone, PartialEq)]
-    struct Test {
-        pub id: u32,
+        // The idle reader doesn't hold back events anymore
+        assert_eq!(buffer.last_index.size, 4);
+        assert_eq!(buffer.try_read(&mut idle).err(), Some(ReadError::Expired));
+        assert_eq!(buffer.meta.active, 1);
+        assert_eq!(buffer.resume(&mut paused), 9);
+
+        // The slot gets reused, dropping the expired id doesn't free it again
+        let mut reused = buffer.new_reader_id();
+        assert_eq!(reused.id, idle.id);
+        drop(idle);
+        buffer.drain_vec_write(&mut events(2));
+        assert!(buffer.would_write());
+        assert_eq!(buffer.read(&mut reused).len(), 2);
     }
 
-    #[derive(Debug, Clone, PartialEq)]
-    struct Test2 {
-        pub id: u32,
+    #[test]
+    #[should_panic(expected = "expired")]
+    fn test_reader_expiry_peek_panics() {
+        let mut buffer = RingBuffer::<Test>::new(4);
+        buffer.set_reader_expiry(Some(0));
+        let reader_id = buffer.new_reader_id();
+        buffer.drain_vec_write(&mut events(1));
+        buffer.drain_vec_write(&mut events(1));
+
+        buffer.peek(&reader_id);
     }
 
     #[test]
-    fn test_size() {
-        let mut buffer = RingBuffer::<i32>::new(4);
+    fn test_drain() {
+        let mut buffer = RingBuffer::<Test>::new(4);
+        let mut reader_id = buffer.new_reader_id();
+        let mut other = buffer.new_reader_id();
+        buffer.drain_vec_write(&mut events(3));
+        assert_eq!(buffer.read(&mut reader_id).len(), 3);
+        buffer.drain_vec_write(&mut events(3));
 
-        buffer.single_write(55);
+        // `other` still needs the events
+        assert_eq!(buffer.drain(&mut reader_id).err(), Some(ReadError::Shared));
+        assert_eq!(buffer.read(&mut other).len(), 6);
 
-        let mut reader = buffer.new_reader_id();
+        assert_eq!(
+            buffer.drain(&mut reader_id).unwrap().collect::<Vec<_>>(),
+            events(3)
+        );
+        assert_eq!(buffer.data.num_initialized(), 0);
+        assert_eq!(buffer.pending(&reader_id), 0);
 
-        buffer.iter_write(0..16);
-        assert_eq!(buffer.read(&mut reader).len(), 16);
+        // Elements which weren't yielded get dropped
+        buffer.drain_vec_write(&mut events(2));
+        assert_eq!(buffer.read(&mut other).len(), 2);
+        {
+            let mut drain = buffer.drain(&mut reader_id).unwrap();
+            assert_eq!(drain.next(), Some(Test { id: 0 }));
+        }
+        assert_eq!(buffer.data.num_initialized(), 0);
 
-        buffer.iter_write(0..6);
-        assert_eq!(buffer.read(&mut reader).len(), 6);
+        buffer.drain_vec_write(&mut events(5));
+        assert_eq!(buffer.read(&mut reader_id).len(), 5);
+        assert_eq!(buffer.read(&mut other).len(), 5);
+        assert_eq!(buffer.oldest_retained(), 8);
     }
 
     #[test]
-    fn test_circular() {
-        let mut buffer = RingBuffer::<i32>::new(4);
-
-        buffer.single_write(55);
+    fn test_drain_paused() {
+        let mut buffer = RingBuffer::<String>::new(4);
+        let mut reader_id = buffer.new_reader_id();
+        let mut paused = buffer.new_reader_id();
+        buffer.iter_write((0..4).map(|i| i.to_string()));
+        buffer.pause(&mut paused);
+        assert_eq!(buffer.seek(&mut paused, 0), Ok(()));
+
+        assert_eq!(buffer.drain(&mut reader_id).unwrap().count(), 4);
+        assert_eq!(buffer.read(&mut paused).len(), 0);
+        assert_eq!(buffer.resume_from_oldest(&mut paused), 4);
+        assert_eq!(buffer.read(&mut paused).len(), 0);
+
+        buffer.single_write("4".to_string());
+        assert_eq!(buffer.read(&mut paused).collect::<Vec<_>>(), vec!["4"]);
+    }
 
-        let mut reader = buffer.new_reader_id();
+    #[test]
+    fn test_read_slices() {
+        let mut buffer = RingBuffer::<i32>::new(4);
+        let mut reader_id = buffer.new_reader_id();
+        assert_eq!(buffer.read_slices(&mut reader_id), (&[][..], &[][..]));
 
-        buffer.iter_write(0..4);
+        buffer.iter_write(0..3);
         assert_eq!(
-            buffer.read(&mut reader).cloned().collect::<Vec<_>>(),
-            vec![0, 1, 2, 3]
+            buffer.read_slices(&mut reader_id),
+            (&[0, 1, 2][..], &[][..])
         );
+
+        buffer.iter_write(3..6);
+        assert_eq!(buffer.peek(&reader_id).as_slices(), (&[3][..], &[4, 5][..]));
+        let mut iter = buffer.read(&mut reader_id);
+        iter.next();
+        assert_eq!(iter.as_slices(), (&[4, 5][..], &[][..]));
     }
 
     #[test]
-    fn test_empty_write() {
-        let mut buffer = RingBuffer::<Test>::new(10);
-        buffer.drain_vec_write(&mut vec![]);
-        assert_eq!(buffer.data.num_initialized(), 0);
+    fn test_iter_random_access() {
+        let mut buffer = RingBuffer::<i32>::new(4);
+        let mut reader_id = buffer.new_reader_id();
+        buffer.iter_write(0..3);
+        buffer.read(&mut reader_id);
+        buffer.iter_write(3..7);
+
+        let mut iter = buffer.peek(&reader_id);
+        assert_eq!(iter.get(3), Some(&6));
+        assert_eq!(iter.get(4), None);
+        assert_eq!(iter.nth(1), Some(&4));
+        assert_eq!(iter.next_sequence(), 5);
+        assert_eq!(iter.next_back(), Some(&6));
+        assert_eq!(iter.len(), 1);
+        assert_eq!(iter.nth(1), None);
+        assert_eq!(iter.len(), 0);
+
+        let iter = buffer.peek(&reader_id);
+        assert_eq!(iter.clone().last(), Some(&6));
+        assert_eq!(iter.clone().rev().collect::<Vec<_>>(), vec![&6, &5, &4, &3]);
+        assert_eq!(iter.sequenced().rev().nth(1), Some((5, &5)));
+
+        // Skipping with a tracked read consumes the skipped elements
+        assert_eq!(buffer.read_tracked(&mut reader_id).nth(2), Some(&5));
+        assert_eq!(buffer.read(&mut reader_id).collect::<Vec<_>>(), vec![&6]);
     }
 
     #[test]
-    fn test_too_large_write() {
-        let mut buffer = RingBuffer::<Test>::new(10);
-        // Events just go off into the void if there's no reader registered.
-        let _reader = buffer.new_reader_id();
-        buffer.drain_vec_write(&mut events(15));
-        assert_eq!(buffer.data.num_initialized(), 15);
+    fn test_read_batches() {
+        let mut buffer = RingBuffer::<i32>::new_bounded(4, 4);
+        let mut reader_id = buffer.new_reader_id();
+        buffer.iter_write(0..2);
+        buffer.iter_write(2..3);
+        buffer.iter_write(None);
+        buffer.iter_write(3..4);
+
+        let batches = buffer
+            .read_batches(&mut reader_id)
+            .map(|batch| batch.cloned().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(batches, vec![vec![0, 1], vec![2], vec![3]]);
+        assert_eq!(buffer.read_batches(&mut reader_id).count(), 0);
+
+        // Partially overwritten batches are cut off
+        buffer.iter_write(4..7);
+        buffer.iter_write(7..9);
+        let batches = buffer
+            .read_batches(&mut reader_id)
+            .map(|batch| batch.cloned().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(batches, vec![vec![5, 6], vec![7, 8]]);
+        let starts = buffer.batches.iter().map(|batch| batch.start);
+        assert_eq!(starts.collect::<Vec<_>>(), vec![4, 7]);
     }
 
     #[test]
-    fn test_empty_read() {
-        let mut buffer = RingBuffer::<Test>::new(10);
+    fn test_read_timestamped() {
+        use crate::clock::ManualClock;
+
+        let clock = ManualClock::new();
+        let mut buffer = RingBuffer::<i32>::new_bounded(4, 4);
+        buffer.set_clock(Box::new(clock.clone()));
         let mut reader_id = buffer.new_reader_id();
-        let data = buffer.read(&mut reader_id);
-        assert_eq!(Vec::<Test>::default(), data.cloned().collect::<Vec<_>>())
+        buffer.iter_write(0..2);
+        clock.advance(Duration::from_secs(1));
+        buffer.iter_write(2..3);
+        clock.advance(Duration::from_secs(1));
+        buffer.iter_write(3..6);
+
+        let secs = |iter: TimestampedIterator<i32>| {
+            iter.map(|(time, &elem)| (time.as_secs(), elem))
+                .collect::<Vec<_>>()
+        };
+        // The first two elements got overwritten
+        assert_eq!(
+            secs(buffer.read_timestamped(&mut reader_id)),
+            vec![(1, 2), (2, 3), (2, 4), (2, 5)]
+        );
+        assert_eq!(secs(buffer.read_timestamped(&mut reader_id)), vec![]);
     }
 
     #[test]
-    fn test_empty_read_write_before_id() {
-        let mut buffer = RingBuffer::<Test>::new(10);
-        buffer.drain_vec_write(&mut events(2));
+    fn test_write_growing() {
+        let mut buffer = RingBuffer::<i32>::new(4);
         let mut reader_id = buffer.new_reader_id();
-        let data = buffer.read(&mut reader_id);
-        assert_eq!(Vec::<Test>::default(), data.cloned().collect::<Vec<_>>())
+        assert_eq!(buffer.iter_write((0..10).filter(|i| i % 2 == 0)), 0..5);
+        assert_eq!(buffer.iter_write((0..0).filter(|_| true)), 5..5);
+        buffer.iter_write(5..8);
+        assert_eq!(buffer.iter_write((0..2).flat_map(|i| vec![i; 4])), 8..16);
+
+        let batches = buffer
+            .read_batches(&mut reader_id)
+            .map(|batch| batch.cloned().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            batches,
+            vec![
+                vec![0, 2, 4, 6, 8],
+                vec![5, 6, 7],
+                vec![0, 0, 0, 0, 1, 1, 1, 1]
+            ]
+        );
+        assert_eq!(buffer.last_index.size, 16);
     }
 
     #[test]
-    fn test_read() {
-        let mut buffer = RingBuffer::<Test>::new(10);
+    fn test_write_growing_bounded() {
+        let mut buffer = RingBuffer::<i32>::new_bounded(2, 4);
         let mut reader_id = buffer.new_reader_id();
-        buffer.drain_vec_write(&mut events(2));
+        buffer.iter_write((0..7).filter(|_| true));
+
         assert_eq!(
-            vec![Test { id: 0 }, Test { id: 1 }],
-            buffer.read(&mut reader_id).cloned().collect::<Vec<_>>()
+            buffer.try_read(&mut reader_id).err(),
+            Some(ReadError::Lagged { missed: 3 })
         );
-
         assert_eq!(
-            Vec::<Test>::new(),
-            buffer.read(&mut reader_id).cloned().collect::<Vec<_>>()
+            buffer.read(&mut reader_id).cloned().collect::<Vec<_>>(),
+            vec![3, 4, 5, 6]
         );
     }
 
     #[test]
-    fn test_write_overflow() {
-        let mut buffer = RingBuffer::<Test>::new(3);
+    fn test_write_iter_panics() {
+        use std::panic::{AssertUnwindSafe, catch_unwind};
+
+        let mut buffer = RingBuffer::<i32>::new(4);
         let mut reader_id = buffer.new_reader_id();
-        buffer.drain_vec_write(&mut events(4));
-        let data = buffer.read(&mut reader_id);
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            buffer.iter_write((0..6).map(|i| if i < 5 { i } else { panic!("Boom") }));
+        }));
+        assert!(result.is_err());
+
+        // The elements written before the panic form a complete write
+        buffer.iter_write(5..7);
+        let batches = buffer
+            .read_batches(&mut reader_id)
+            .map(|batch| batch.cloned().collect::<Vec<_>>())
+            .collect::<Vec<_>>();
+        assert_eq!(batches, vec![vec![0, 1, 2, 3, 4], vec![5, 6]]);
+        assert_eq!(buffer.pending(&reader_id), 0);
+    }
+
+    #[test]
+    fn test_write_panics_bounded() {
+        use std::panic::{AssertUnwindSafe, catch_unwind};
+
+        let mut buffer = RingBuffer::<String>::new_bounded(4, 4);
+        let mut reader_id = buffer.new_reader_id();
+        buffer.iter_write((0..4).map(|i| i.to_string()));
+
+        // Nothing got overwritten, so the reader doesn't lag
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            buffer.iter_write((4..8).map(|_| -> String { panic!("Boom") }));
+        }));
+        assert!(result.is_err());
+        assert_eq!(buffer.pending(&reader_id), 4);
+
+        // The reader misses the element handed to `f` and skips the slot
+        // which was left empty
+        let mut calls = 0;
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            buffer.write_with(2, |_| {
+                calls += 1;
+                if calls == 2 {
+                    panic!("Boom");
+                }
+                "4".to_string()
+            });
+        }));
+        assert!(result.is_err());
         assert_eq!(
-            vec![
-                Test { id: 0 },
-                Test { id: 1 },
-                Test { id: 2 },
-                Test { id: 3 },
-            ],
-            data.cloned().collect::<Vec<_>>()
+            buffer.try_read(&mut reader_id).err(),
+            Some(ReadError::Lagged { missed: 2 })
+        );
+        assert_eq!(
+            buffer.read(&mut reader_id).cloned().collect::<Vec<_>>(),
+            vec!["2", "3", "4"]
         );
     }
 
-    /// If you're getting a compilation error here this test has failed!
     #[test]
-    fn test_send_sync() {
-        trait SendSync: Send + Sync {
-            fn is_send_sync() -> bool;
-        }
+    fn test_drop_panics() {
+        use std::panic::{AssertUnwindSafe, catch_unwind};
 
-        impl<T> SendSync for T
-        where
-            T: Send + Sync,
-        {
-            fn is_send_sync() -> bool {
-                true
+        #[derive(Debug)]
+        struct Bomb(u32, bool);
+
+        impl Drop for Bomb {
+            fn drop(&mut self) {
+                if self.1 && !std::thread::panicking() {
+                    panic!("Boom");
+                }
             }
         }
 
-        assert!(RingBuffer::<Test>::is_send_sync());
-        assert!(ReaderId::<Test>::is_send_sync());
+        let mut buffer = RingBuffer::<Bomb>::new(2);
+        buffer.iter_write(vec![Bomb(0, true), Bomb(1, false)]);
+        let result = catch_unwind(AssertUnwindSafe(|| {
+            buffer.iter_write(vec![Bomb(2, false), Bomb(3, false)]);
+        }));
+        assert!(result.is_err());
+
+        // The element replacing the panicking one has been written
+        assert_eq!(buffer.written, 3);
+        let mut reader_id = buffer.new_reader_id_at(0);
+        let ids = buffer.read(&mut reader_id).map(|bomb| bomb.0);
+        assert_eq!(ids.collect::<Vec<_>>(), vec![1, 2]);
+
+        buffer.single_write(Bomb(4, true));
+        let result = catch_unwind(AssertUnwindSafe(|| drop(buffer)));
+        assert!(result.is_err());
     }
 
     #[test]
-    fn test_reader_reuse() {
-        let mut buffer = RingBuffer::<Test>::new(3);
-        {
-            let _reader_id = buffer.new_reader_id();
-        }
-        let _reader_id = buffer.new_reader_id();
-        assert_eq!(_reader_id.id, 0);
-        assert_eq!(buffer.meta.readers.len(), 1);
+    fn test_write_with() {
+        let mut buffer = RingBuffer::<Vec<u32>>::new(2);
+        let mut reader_id = buffer.new_reader_id();
+        assert_eq!(
+            buffer.write_with(2, |old| {
+                assert!(old.is_none());
+                Vec::with_capacity(16)
+            }),
+            0..2
+        );
+        assert_eq!(buffer.read(&mut reader_id).len(), 2);
+
+        // The slots can be reused once the reader is done with them
+        let seq = buffer.single_write_with(|old| {
+            let mut vec = old.unwrap();
+            assert_eq!(vec.capacity(), 16);
+            vec.push(1);
+            vec
+        });
+        assert_eq!(seq, 2);
+
+        // Unread elements are never handed out
+        buffer.write_with(2, |old| old.unwrap_or_default());
+        assert_eq!(buffer.last_index.size, 4);
+        let data = buffer.read(&mut reader_id).cloned().collect::<Vec<_>>();
+        assert_eq!(data, vec![vec![1], vec![], vec![]]);
+        assert_eq!(buffer.oldest_retained(), 1);
     }
 
     #[test]
-    fn test_prevent_excess_growth() {
-        let mut buffer = RingBuffer::<Test>::new(3);
+    fn test_shrink_to() {
+        let mut buffer = RingBuffer::<Test>::new(4);
         let mut reader_id = buffer.new_reader_id();
-        println!("Initial buffer state: {:#?}", buffer);
-        println!("--- first write ---");
-        buffer.drain_vec_write(&mut events(2));
-        println!("--- second write ---");
-        buffer.drain_vec_write(&mut events(2));
-        println!("--- writes complete ---");
-        // we wrote 0,1,0,1, if the buffer grew correctly we'll get all of these back.
+        let mut slow = buffer.new_reader_id();
+        let mut paused = buffer.new_reader_id();
+        buffer.pause(&mut paused);
+        buffer.drain_vec_write(&mut events(30));
+        assert_eq!(buffer.capacity(), 32);
+        assert_eq!(buffer.read(&mut reader_id).len(), 30);
         assert_eq!(
-            vec![
-                Test { id: 0 },
-                Test { id: 1 },
-                Test { id: 0 },
-                Test { id: 1 },
-            ],
-            buffer.read(&mut reader_id).cloned().collect::<Vec<_>>()
+            buffer.read_tracked(&mut slow).nth(24),
+            Some(&Test { id: 24 })
         );
+        buffer.drain_vec_write(&mut events(2));
+        assert_eq!(buffer.read(&mut reader_id).len(), 2);
+
+        // The unread elements of `slow` are kept
+        buffer.shrink_to(0);
+        assert_eq!(buffer.capacity(), 7);
+        assert_eq!(buffer.oldest_retained(), 25);
+        assert_eq!(buffer.pending(&reader_id), 0);
+        assert_eq!(buffer.pending(&paused), 0);
+        let data = buffer.read(&mut slow).cloned().collect::<Vec<_>>();
+        let mut expected = events(30).split_off(25);
+        expected.extend(events(2));
+        assert_eq!(data, expected);
+
+        buffer.drain_vec_write(&mut events(6));
+        assert_eq!(buffer.capacity(), 7);
+        assert_eq!(buffer.read(&mut reader_id).len(), 6);
+        assert_eq!(buffer.read(&mut slow).len(), 6);
+        assert_eq!(buffer.resume(&mut paused), 38);
+    }
 
-        buffer.drain_vec_write(&mut events(4));
-        // After writing 4 more events the buffer should have no reason to grow beyond 6
-        // (2 * 3).
-        assert_eq!(buffer.data.num_initialized(), 6);
+    #[test]
+    fn test_shrink_to_paused() {
+        let mut buffer = RingBuffer::<String>::new(4);
+        let mut reader_id = buffer.new_reader_id();
+        let mut paused = buffer.new_reader_id();
+        buffer.pause(&mut paused);
+        buffer.iter_write((0..10).map(|i| i.to_string()));
+        assert_eq!(buffer.capacity(), 16);
+        assert_eq!(buffer.read(&mut reader_id).len(), 10);
+        assert_eq!(buffer.seek(&mut paused, 2), Ok(()));
+
+        buffer.shrink_to(0);
+        assert_eq!(buffer.capacity(), 2);
+        assert_eq!(buffer.pending(&paused), 0);
+        assert_eq!(buffer.read(&mut paused).len(), 0);
+
+        assert_eq!(buffer.resume_from_oldest(&mut paused), 8);
+        buffer.single_write("10".to_string());
+        assert_eq!(buffer.read(&mut paused).collect::<Vec<_>>(), vec!["10"]);
+    }
+
+    #[test]
+    fn test_shrink_to_drop_panics() {
+        use std::panic::{AssertUnwindSafe, catch_unwind};
+
+        #[derive(Debug)]
+        struct Bomb(u32, bool);
+
+        impl Drop for Bomb {
+            fn drop(&mut self) {
+                if self.1 && !std::thread::panicking() {
+                    panic!("Boom");
+                }
+            }
+        }
+
+        let mut buffer = RingBuffer::<Bomb>::new(4);
+        let mut reader_id = buffer.new_reader_id();
+        buffer.iter_write((0..16).map(|i| Bomb(i, i == 3)));
+        assert_eq!(buffer.capacity(), 16);
+        assert_eq!(buffer.read(&mut reader_id).len(), 16);
+
+        let result = catch_unwind(AssertUnwindSafe(|| buffer.shrink_to(0)));
+        assert!(result.is_err());
+
+        // The buffer got shrunk anyway
+        assert_eq!(buffer.capacity(), 2);
+        assert_eq!(buffer.oldest_retained(), 16);
+        buffer.iter_write((16..20).map(|i| Bomb(i, false)));
+        let ids = buffer.read(&mut reader_id).map(|bomb| bomb.0);
+        assert_eq!(ids.collect::<Vec<_>>(), vec![16, 17, 18, 19]);
+    }
+
+    #[test]
+    fn test_auto_shrink() {
+        let mut buffer = RingBuffer::<Test>::new(4);
+        buffer.set_auto_shrink(true);
+        let mut reader_id = buffer.new_reader_id();
+        buffer.drain_vec_write(&mut events(10));
+        assert_eq!(buffer.capacity(), 16);
+
+        // The reader didn't catch up yet
+        buffer.drain_vec_write(&mut events(1));
+        assert_eq!(buffer.capacity(), 16);
+        assert_eq!(buffer.read(&mut reader_id).len(), 11);
+
+        buffer.drain_vec_write(&mut events(3));
+        assert_eq!(buffer.capacity(), 4);
         assert_eq!(
-            vec![
-                Test { id: 0 },
-                Test { id: 1 },
-                Test { id: 2 },
-                Test { id: 3 },
-            ],
-            buffer.read(&mut reader_id).cloned().collect::<Vec<_>>()
+            buffer.read(&mut reader_id).cloned().collect::<Vec<_>>(),
+            events(3)
         );
     }
 
     #[test]
-    fn test_write_slice() {
-        let mut buffer = RingBuffer::<Test>::new(10);
+    fn test_auto_shrink_retention() {
+        let mut buffer = RingBuffer::<i32>::new(4);
+        buffer.set_auto_shrink(true);
+        buffer.set_retention(6);
         let mut reader_id = buffer.new_reader_id();
-        buffer.iter_write(events(2));
-        let data = buffer.read(&mut reader_id);
+        buffer.iter_write(0..20);
+        assert_eq!(buffer.read(&mut reader_id).len(), 20);
+
+        buffer.single_write(20);
+        assert_eq!(buffer.capacity(), 7);
+        assert_eq!(buffer.read(&mut reader_id).len(), 1);
+        let data = buffer.data.data.as_ptr();
+        for i in 21..40 {
+            buffer.single_write(i);
+            assert_eq!(buffer.read(&mut reader_id).len(), 1);
+        }
+        assert_eq!(buffer.capacity(), 7);
+        assert_eq!(buffer.data.data.as_ptr(), data);
+        assert_eq!(buffer.oldest_retained(), 33);
+    }
+
+    #[test]
+    fn test_growth_policy() {
+        use crate::growth::Linear;
+
+        let mut buffer = RingBuffer::<i32>::with_growth_policy(4, Box::new(Linear::new(3)));
+        let mut reader_id = buffer.new_reader_id();
+        buffer.iter_write(0..5);
+        assert_eq!(buffer.capacity(), 7);
+        buffer.iter_write(5..15);
+        assert_eq!(buffer.capacity(), 16);
         assert_eq!(
-            vec![Test { id: 0 }, Test { id: 1 }],
-            data.cloned().collect::<Vec<_>>()
+            buffer.read(&mut reader_id).cloned().collect::<Vec<_>>(),
+            (0..15).collect::<Vec<_>>()
         );
     }
 
     #[test]
-    fn iter_write_empty() {
-        let mut buffer = RingBuffer::<Test>::new(10);
+    fn test_seek_paused() {
+        let mut buffer = RingBuffer::<Test>::new(4);
         let mut reader_id = buffer.new_reader_id();
-        buffer.iter_write(Vec::new());
-        let mut data = buffer.read(&mut reader_id);
-        assert_eq!(None, data.next());
+        buffer.drain_vec_write(&mut events(4));
+        buffer.pause(&mut reader_id);
+        buffer.drain_vec_write(&mut events(2));
+
+        // The reader stays at the write position until it's resumed
+        assert_eq!(buffer.seek(&mut reader_id, 3), Ok(()));
+        assert_eq!(buffer.pending(&reader_id), 0);
+        assert_eq!(buffer.read_tracked(&mut reader_id).len(), 0);
+        assert!(!buffer.would_write());
+        buffer.drain_vec_write(&mut events(4));
+        assert_eq!(buffer.capacity(), 4);
+
+        assert_eq!(buffer.resume_from_oldest(&mut reader_id), 3);
+        assert_eq!(
+            buffer.read(&mut reader_id).cloned().collect::<Vec<_>>(),
+            events(4)
+        );
     }
 
     fn events(n: u32) -> Vec<Test> {
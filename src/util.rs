@@ -1,8 +1,15 @@
 use std::{
+    collections::hash_map::RandomState,
     fmt::{self, Debug, Formatter},
-    sync::Arc,
+    hash::BuildHasher,
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
 };
 
+static NEXT_SERIAL: AtomicUsize = AtomicUsize::new(0);
+
 /// A unique ID that can be used to assert two objects refer to another common
 /// object.
 ///
@@ -16,6 +23,7 @@ use std::{
 pub struct InstanceId {
     inner: Arc<u8>,
     msg: &'static str,
+    serial: u64,
 }
 
 impl InstanceId {
@@ -24,9 +32,21 @@ impl InstanceId {
         InstanceId {
             inner: Arc::default(),
             msg,
+            // Randomly keyed, so instance ids of different processes differ
+            serial: RandomState::new().hash_one(NEXT_SERIAL.fetch_add(1, Ordering::Relaxed)),
         }
     }
 
+    /// Returns a number which is unique among all instance ids, even those
+    /// created by other processes (with overwhelming probability).
+    ///
+    /// Unlike `as_usize`, this is never reused, even after the instance id
+    /// got dropped.
+    #[inline]
+    pub fn serial(&self) -> u64 {
+        self.serial
+    }
+
     /// Returns the unique `usize` representation which is used for all the
     /// assertions.
     #[inline]
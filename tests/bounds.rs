@@ -1,7 +1,11 @@
+use std::panic::{RefUnwindSafe, UnwindSafe};
+
 use shrev::*;
 
 fn is_sync<T: Sync>() {}
 fn is_send<T: Send>() {}
+fn is_unwind_safe<T: UnwindSafe>() {}
+fn is_ref_unwind_safe<T: RefUnwindSafe>() {}
 
 #[test]
 fn event_channel_bounds() {
@@ -15,8 +19,20 @@ fn reader_id_bounds() {
     is_sync::<ReaderId<i32>>();
 }
 
+#[test]
+fn filtered_reader_id_bounds() {
+    is_send::<FilteredReaderId<i32>>();
+    is_sync::<FilteredReaderId<i32>>();
+}
+
 #[test]
 fn event_iterator_bounds() {
     is_send::<EventIterator<'static, i32>>();
     is_sync::<EventIterator<'static, i32>>();
 }
+
+#[test]
+fn event_channel_unwind_safe() {
+    is_unwind_safe::<EventChannel<i32>>();
+    is_ref_unwind_safe::<EventChannel<i32>>();
+}
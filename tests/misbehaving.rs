@@ -0,0 +1,144 @@
+use std::panic::{AssertUnwindSafe, catch_unwind};
+
+use shrev::*;
+
+/// An iterator whose `len` is off by `error`.
+#[derive(Debug)]
+struct Lying {
+    error: isize,
+    iter: std::ops::Range<i32>,
+}
+
+impl Lying {
+    fn new(iter: std::ops::Range<i32>, error: isize) -> Self {
+        Lying { error, iter }
+    }
+}
+
+impl Iterator for Lying {
+    type Item = i32;
+
+    fn next(&mut self) -> Option<i32> {
+        self.iter.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = (self.iter.len() as isize + self.error).max(0) as usize;
+
+        (len, Some(len))
+    }
+}
+
+impl ExactSizeIterator for Lying {}
+
+fn read_all(channel: &EventChannel<i32>, reader: &mut ReaderId<i32>) -> Vec<i32> {
+    channel.read(reader).cloned().collect()
+}
+
+#[test]
+fn iter_write_more_than_len() {
+    let mut channel = EventChannel::with_capacity(4);
+    let mut reader = channel.register_reader();
+    channel.iter_write(0..3);
+
+    assert_eq!(channel.iter_write(Lying::new(3..12, -8)), 3..12);
+    assert_eq!(read_all(&channel, &mut reader), (0..12).collect::<Vec<_>>());
+
+    channel.iter_write(Lying::new(12..20, -8));
+    channel.iter_write(20..22);
+    assert_eq!(
+        read_all(&channel, &mut reader),
+        (12..22).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn iter_write_fewer_than_len() {
+    let mut channel = EventChannel::with_capacity(4);
+    let mut reader = channel.register_reader();
+
+    assert_eq!(channel.iter_write(Lying::new(0..2, 5)), 0..2);
+    assert_eq!(channel.iter_write(Lying::new(2..2, 3)), 2..2);
+    channel.iter_write(2..6);
+    assert_eq!(read_all(&channel, &mut reader), (0..6).collect::<Vec<_>>());
+}
+
+#[test]
+fn iter_write_more_than_len_bounded() {
+    let mut channel = EventChannel::with_capacity_bounded(2, 4);
+    let mut reader = channel.register_reader();
+
+    channel.iter_write(Lying::new(0..7, -5));
+    assert_eq!(
+        channel.try_read(&mut reader).err(),
+        Some(ReadError::Lagged { missed: 3 })
+    );
+    assert_eq!(read_all(&channel, &mut reader), vec![3, 4, 5, 6]);
+}
+
+#[test]
+fn iter_write_fewer_than_len_bounded() {
+    let mut channel = EventChannel::with_capacity_bounded(4, 4);
+    let mut reader = channel.register_reader();
+    channel.iter_write(0..3);
+
+    // Nothing gets overwritten, so the reader doesn't lag
+    assert_eq!(channel.iter_write(Lying::new(3..3, 5)), 3..3);
+    assert_eq!(
+        channel
+            .try_read(&mut reader)
+            .unwrap()
+            .cloned()
+            .collect::<Vec<_>>(),
+        vec![0, 1, 2]
+    );
+
+    channel.iter_write(3..6);
+    assert_eq!(channel.iter_write(Lying::new(6..7, 4)), 6..7);
+    assert_eq!(
+        channel
+            .try_read(&mut reader)
+            .unwrap()
+            .cloned()
+            .collect::<Vec<_>>(),
+        vec![3, 4, 5, 6]
+    );
+
+    // Only the elements actually overwritten are missed
+    channel.iter_write(7..10);
+    channel.iter_write(Lying::new(10..12, 4));
+    assert_eq!(
+        channel.try_read(&mut reader).err(),
+        Some(ReadError::Lagged { missed: 1 })
+    );
+    assert_eq!(read_all(&channel, &mut reader), vec![8, 9, 10, 11]);
+}
+
+#[test]
+fn try_iter_write_more_than_len() {
+    let mut channel = EventChannel::with_capacity_bounded(4, 4);
+    let mut reader = channel.register_reader();
+
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        let _ = channel.try_iter_write(Lying::new(0..10, -8));
+    }));
+    assert!(result.is_err());
+
+    // The elements up to the reported length got written, nothing else
+    assert_eq!(read_all(&channel, &mut reader), vec![0, 1]);
+    assert!(channel.try_iter_write(10..14).is_ok());
+    assert_eq!(
+        read_all(&channel, &mut reader),
+        (10..14).collect::<Vec<_>>()
+    );
+}
+
+#[test]
+fn try_iter_write_fewer_than_len() {
+    let mut channel = EventChannel::with_capacity_bounded(4, 4);
+    let mut reader = channel.register_reader();
+
+    assert_eq!(channel.try_iter_write(Lying::new(0..1, 3)).unwrap(), 0..1);
+    assert!(channel.try_iter_write(1..4).is_ok());
+    assert_eq!(read_all(&channel, &mut reader), (0..4).collect::<Vec<_>>());
+}
@@ -0,0 +1,31 @@
+#![cfg(feature = "serde")]
+
+use shrev::*;
+
+#[test]
+fn cursor_round_trip() {
+    let mut channel = EventChannel::new();
+    let mut reader = channel.register_reader();
+    channel.iter_write(0..4);
+    let cursor = channel.cursor(&reader);
+    assert_eq!(channel.read(&mut reader).len(), 4);
+
+    let json = serde_json::to_string(&cursor).unwrap();
+    let restored: ReaderCursor = serde_json::from_str(&json).unwrap();
+    assert_eq!(restored, cursor);
+    channel.restore(&mut reader, restored).unwrap();
+    assert_eq!(
+        channel.read(&mut reader).cloned().collect::<Vec<_>>(),
+        vec![0, 1, 2, 3]
+    );
+
+    // Cursors of another channel are rejected, even if they're serialized
+    let mut other = EventChannel::<i32>::new();
+    let other_reader = other.register_reader();
+    let json = serde_json::to_string(&other.cursor(&other_reader)).unwrap();
+    let foreign: ReaderCursor = serde_json::from_str(&json).unwrap();
+    assert_eq!(
+        channel.restore(&mut reader, foreign),
+        Err(SeekError::ForeignCursor)
+    );
+}
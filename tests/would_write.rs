@@ -46,3 +46,18 @@ fn recreated() {
 
     assert!(channel.would_write());
 }
+
+#[test]
+fn might_write_shared() {
+    let mut channel = EventChannel::new();
+    assert!(!channel.might_write());
+
+    let r = channel.register_reader();
+    assert!(channel.might_write());
+
+    drop(r);
+    // The drop is only noticed once the channel gets maintained
+    assert!(channel.might_write());
+    assert!(!channel.would_write());
+    assert!(!channel.might_write());
+}